@@ -0,0 +1,112 @@
+use anyhow::Result;
+use image::imageops::FilterType;
+use std::{collections::VecDeque, fmt::Write, path::Path, sync::Mutex};
+
+use crate::http_client;
+
+/// How many recently downloaded images to keep, since songs repeat in the rotation.
+const CACHE_CAPACITY: usize = 16;
+
+/// Recently downloaded images and their URLs, least recently used first.
+static CACHE: Mutex<VecDeque<(String, Vec<u8>)>> = Mutex::new(VecDeque::new());
+
+/// Download album art image from `url`, or reuse it if it was downloaded recently.
+/// Failed downloads are not cached, so they are retried next time.
+pub async fn download(url: &str) -> Result<Vec<u8>> {
+    if let Some(image) = get_cached(url) {
+        log::debug!("Reusing cached album art {}", url);
+        return Ok(image);
+    }
+
+    let image = download_uncached(url).await?;
+    add_to_cache(url, image.clone());
+    Ok(image)
+}
+
+/// Download album art ahead of time, like the next song's, so it shows up right away when needed.
+pub async fn prefetch(url: String) {
+    if let Err(e) = download(&url).await {
+        log::debug!("Failed to prefetch album art: {:#}", e);
+    }
+}
+
+fn get_cached(url: &str) -> Option<Vec<u8>> {
+    let mut cache = CACHE.lock().unwrap();
+    let index = cache.iter().position(|(cached_url, _)| cached_url == url)?;
+    // Move it to the back, as the most recently used
+    let entry = cache.remove(index)?;
+    let image = entry.1.clone();
+    cache.push_back(entry);
+    Some(image)
+}
+
+fn add_to_cache(url: &str, image: Vec<u8>) {
+    let mut cache = CACHE.lock().unwrap();
+    cache.retain(|(cached_url, _)| cached_url != url);
+    if cache.len() >= CACHE_CAPACITY {
+        cache.pop_front();
+    }
+    cache.push_back((url.to_owned(), image));
+}
+
+async fn download_uncached(url: &str) -> Result<Vec<u8>> {
+    let bytes = http_client::client()
+        .get(url)
+        .timeout(http_client::request_timeout())
+        .send()
+        .await
+        .map_err(http_client::explain_timeout)?
+        .error_for_status()?
+        .bytes()
+        .await
+        .map_err(http_client::explain_timeout)?;
+    Ok(bytes.to_vec())
+}
+
+/// Download album art image from `url` and write it to `path`, overwriting the existing file.
+///
+/// The image is written to a temporary file first and then renamed,
+/// so external tools watching `path` never read a half-written image.
+pub async fn download_to_file(url: &str, path: &Path) -> Result<()> {
+    let image = download(url).await?;
+
+    let temp_path = path.with_extension("tmp");
+
+    tokio::fs::write(&temp_path, &image).await?;
+    tokio::fs::rename(&temp_path, path).await?;
+
+    Ok(())
+}
+
+/// Render an image as `width` columns of ANSI true color half-block characters.
+///
+/// Each character cell holds two vertically stacked pixels: the upper one as the
+/// foreground color of `▀` and the lower one as the background color.
+/// Since terminal cells are roughly twice as tall as they are wide, this keeps the aspect ratio.
+pub fn render_as_half_blocks(image: &[u8], width: u32) -> Result<String> {
+    let image = image::load_from_memory(image)?;
+
+    let width = width.max(1);
+    let height =
+        (u64::from(image.height()) * u64::from(width) / u64::from(image.width().max(1))) as u32;
+    let height = height.max(2).div_ceil(2) * 2;
+
+    let image = image
+        .resize_exact(width, height, FilterType::Triangle)
+        .to_rgb8();
+
+    let mut output = String::new();
+    for y in (0..height).step_by(2) {
+        for x in 0..width {
+            let [top_r, top_g, top_b] = image.get_pixel(x, y).0;
+            let [bottom_r, bottom_g, bottom_b] = image.get_pixel(x, y + 1).0;
+            write!(
+                output,
+                "\x1b[38;2;{top_r};{top_g};{top_b}m\x1b[48;2;{bottom_r};{bottom_g};{bottom_b}m▀"
+            )?;
+        }
+        output.push_str("\x1b[0m\n");
+    }
+
+    Ok(output)
+}
@@ -0,0 +1,44 @@
+//! Per-user directories for files kept across runs, like the config file, remembered state and caches.
+
+use directories::ProjectDirs;
+use once_cell::sync::{Lazy, OnceCell};
+use std::path::{Path, PathBuf};
+
+static CACHE_DIR_OVERRIDE: OnceCell<PathBuf> = OnceCell::new();
+
+static CACHE_DIR: Lazy<PathBuf> = Lazy::new(|| {
+    let cache_dir = match CACHE_DIR_OVERRIDE.get() {
+        Some(cache_dir) => cache_dir.clone(),
+        None => match get_project_dirs() {
+            Some(dirs) => dirs.cache_dir().to_owned(),
+            None => return std::env::temp_dir(),
+        },
+    };
+
+    match std::fs::create_dir_all(&cache_dir) {
+        Ok(()) => cache_dir,
+        Err(e) => {
+            log::warn!(
+                "Failed to create cache directory {}, using temp directory instead: {:#}",
+                cache_dir.display(),
+                e
+            );
+            std::env::temp_dir()
+        }
+    }
+});
+
+pub fn get_project_dirs() -> Option<ProjectDirs> {
+    ProjectDirs::from("", "", "code-radio")
+}
+
+/// Use `cache_dir` instead of the default cache directory. Must be called before `cache_dir`.
+pub fn set_cache_dir(cache_dir: PathBuf) {
+    let _ = CACHE_DIR_OVERRIDE.set(cache_dir);
+}
+
+/// Like `~/.cache/code-radio` on Linux, or the directory set by `set_cache_dir`.
+/// Created if it doesn't exist, or falls back to the temp directory if it can't be created.
+pub fn cache_dir() -> &'static Path {
+    CACHE_DIR.as_path()
+}
@@ -0,0 +1,86 @@
+//! What changed between now playing messages, worked out without any output or global state,
+//! so each client decides how to show it.
+
+use crate::models::code_radio::{CodeRadioMessage, Listeners, Song};
+
+/// A change to show, returned by [`AppState::on_message`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UiEvent {
+    /// A different song started. `is_first` is `true` for the first song since launching,
+    /// which is usually already playing, so notifying it is just noise.
+    NewSong {
+        song: Song,
+        is_first: bool,
+    },
+    /// The current song's progress, reported again. `duration` is 0 if it's unknown.
+    SongProgress {
+        elapsed: i64,
+        duration: i64,
+    },
+    ListenersChanged(Listeners),
+    /// A DJ went on air.
+    LiveStarted {
+        streamer_name: String,
+    },
+    LiveEnded,
+}
+
+#[derive(Debug, Default)]
+pub struct AppState {
+    latest_message: Option<CodeRadioMessage>,
+}
+
+impl AppState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn latest_message(&self) -> Option<&CodeRadioMessage> {
+        self.latest_message.as_ref()
+    }
+
+    pub fn current_song_id(&self) -> Option<&str> {
+        self.latest_message
+            .as_ref()
+            .map(|message| message.now_playing.song.id.as_str())
+    }
+
+    /// Take in a new message, and return what changed since the previous one.
+    ///
+    /// Live broadcast changes come first, then the song, then the listeners count.
+    pub fn on_message(&mut self, message: CodeRadioMessage) -> Vec<UiEvent> {
+        let previous_message = self.latest_message.replace(message);
+        let message = self.latest_message.as_ref().unwrap();
+        let mut events = Vec::new();
+
+        let was_live = previous_message.as_ref().map(|m| m.live.is_live);
+        if was_live.is_some_and(|was_live| was_live != message.live.is_live) {
+            events.push(if message.live.is_live {
+                UiEvent::LiveStarted {
+                    streamer_name: message.live.streamer_name.clone(),
+                }
+            } else {
+                UiEvent::LiveEnded
+            });
+        }
+
+        let previous_song_id = previous_message.as_ref().map(|m| &m.now_playing.song.id);
+        if previous_song_id != Some(&message.now_playing.song.id) {
+            events.push(UiEvent::NewSong {
+                song: message.now_playing.song.clone(),
+                is_first: previous_message.is_none(),
+            });
+        } else {
+            events.push(UiEvent::SongProgress {
+                elapsed: message.now_playing.elapsed,
+                duration: message.now_playing.duration,
+            });
+        }
+
+        if previous_message.map(|m| m.listeners).as_ref() != Some(&message.listeners) {
+            events.push(UiEvent::ListenersChanged(message.listeners.clone()));
+        }
+
+        events
+    }
+}
@@ -1,20 +1,476 @@
-use clap::Parser;
+use clap::{ArgEnum, Parser};
+use std::{path::PathBuf, str::FromStr};
+
+use crate::{audio_backend::AudioBackendKind, code_radio_api};
 
 const ABOUT: &str = "A command line music radio client for https://coderadio.freecodecamp.org
 GitHub: https://github.com/JasonWei512/code-radio-cli";
 
 #[derive(Parser, Debug)]
-#[clap(author, version, about = ABOUT)]
+#[clap(author, version, about = ABOUT, args_override_self = true)]
 pub struct Args {
+    /// Read default options from this TOML file, instead of the default config file
+    #[clap(long, value_name = "PATH", conflicts_with = "no-config")]
+    pub config: Option<PathBuf>,
+
+    /// Ignore the config file
+    #[clap(long)]
+    pub no_config: bool,
+
+    /// Do not use colors in output. Also disabled by the NO_COLOR environment variable
+    #[clap(long)]
+    pub no_color: bool,
+
     /// Manually select a station
     #[clap(short, long)]
     pub select_station: bool,
 
-    /// Volume, between 0 and 9
-    #[clap(short, long, default_value_t = 9)]
-    pub volume: u8,
+    /// Send all requests through this proxy, like "http://127.0.0.1:8080" or "socks5://127.0.0.1:1080".
+    /// Defaults to HTTP_PROXY and HTTPS_PROXY environment variables
+    #[clap(long, value_name = "URL")]
+    pub proxy: Option<String>,
+
+    /// Quit after this many minutes, fading out during the last 30 seconds
+    #[clap(long, value_name = "MINUTES")]
+    pub sleep: Option<u64>,
+
+    /// Save the audio stream to this file while playing, like "session.mp3".
+    /// Reconnects append to the same file. A directory with --record-split
+    #[clap(long, value_name = "PATH")]
+    pub record: Option<PathBuf>,
+
+    /// Record each song to its own file, like "01 - Artist - Title.mp3", in the --record directory
+    #[clap(long, requires = "record", conflicts_with = "url")]
+    pub record_split: bool,
+
+    /// Ring the terminal bell and highlight the song when its title or artist contains this,
+    /// ignoring case. Can be repeated
+    #[clap(long, value_name = "SUBSTRING")]
+    pub watch: Vec<String>,
+
+    /// POST current song's info as JSON to this URL on each song change
+    #[clap(long, value_name = "URL")]
+    pub webhook: Option<String>,
+
+    /// Keep cache files, like the latest release info of the update check, in this directory
+    /// instead of the default cache directory
+    #[clap(long, value_name = "PATH")]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Network timeout in seconds, for connecting and for requests other than streams
+    #[clap(long, value_name = "SECONDS", default_value_t = 10)]
+    pub timeout: u64,
+
+    /// Seconds to wait before reconnecting when the now playing info stream drops,
+    /// doubling on each failed attempt up to --reconnect-max
+    #[clap(long, value_name = "SECS", default_value_t = 1)]
+    pub reconnect_initial: u64,
+
+    /// Max seconds to wait before reconnecting when the now playing info stream drops
+    #[clap(long, value_name = "SECS", default_value_t = 20)]
+    pub reconnect_max: u64,
+
+    /// How many times in a row to try reconnecting when the audio stream drops, then exit.
+    /// --max-retries can stop it sooner. 0 to never reconnect
+    #[clap(long, value_name = "N", default_value_t = 5)]
+    pub reconnect_attempts: u32,
+
+    /// Give up and exit after this many reconnection attempts of the now playing info stream
+    /// and the audio stream together, since the last successful connection. 0 for no limit,
+    /// though the audio stream still gives up after --reconnect-attempts
+    #[clap(long, value_name = "N", default_value_t = 0)]
+    pub max_retries: u32,
+
+    /// Replay metadata recorded with --dump-metadata, without network or audio
+    #[clap(long, value_name = "FILE")]
+    pub simulate: Option<PathBuf>,
+
+    /// Replay speed of --simulate, like 2.0 for double speed
+    #[clap(long, value_name = "FACTOR", default_value_t = 1.0)]
+    pub simulate_speed: f64,
+
+    /// Play the station last selected with --select-station
+    #[clap(long, conflicts_with_all = &["select-station", "station"])]
+    pub resume: bool,
+
+    /// Play a station by its name, ID, or mount shortcode (like "low.mp3")
+    #[clap(
+        long,
+        value_name = "NAME_OR_SHORTCODE",
+        conflicts_with = "select-station"
+    )]
+    pub station: Option<String>,
+
+    /// Read ahead this many kilobytes of the audio stream, so short network stalls don't cause
+    /// dropouts. Playback waits for half of it to fill at the start and after a stall. 0 to disable
+    #[clap(long, value_name = "KB", default_value_t = 64)]
+    pub buffer: usize,
+
+    /// Downmix stereo to mono on both speakers, for setups with only one working speaker
+    #[clap(long)]
+    pub mono: bool,
+
+    /// Play this audio stream URL as is, like a plain Icecast MP3 stream, without any now playing
+    /// info from the API. Only volume and elapsed time are displayed
+    #[clap(
+        long,
+        value_name = "STREAM_URL",
+        conflicts_with_all = &["station", "select-station", "resume", "bitrate", "simulate", "oneshot", "tui", "json"]
+    )]
+    pub url: Option<String>,
+
+    /// Play the station whose bitrate is closest to this, like 64 on metered connections
+    #[clap(
+        long,
+        value_name = "KBPS",
+        conflicts_with_all = &["select-station", "station", "resume"]
+    )]
+    pub bitrate: Option<i64>,
+
+    /// Base URL of the AzuraCast instance to get now playing info from
+    #[clap(long, value_name = "BASE", default_value = code_radio_api::DEFAULT_API_URL)]
+    pub api_url: String,
+
+    /// Shortcode of the station on the AzuraCast instance of --api-url
+    #[clap(long, value_name = "SHORTCODE", default_value = code_radio_api::DEFAULT_STATION_SHORTCODE)]
+    pub station_shortcode: String,
+
+    /// Volume, between 0 and 9, or a percentage like "50%". Defaults to the last used volume, or 9
+    #[clap(short, long)]
+    pub volume: Option<Volume>,
+
+    /// Display volume as a percentage instead of 0-9
+    #[clap(long)]
+    pub volume_percent: bool,
+
+    /// Do not remember the volume for the next launch
+    #[clap(long)]
+    pub no_remember_volume: bool,
+
+    /// A named volume, like "night=2", to start at with --preset or switch to by pressing P.
+    /// Can be repeated
+    #[clap(long, value_name = "NAME=VOLUME")]
+    pub volume_preset: Vec<VolumePreset>,
+
+    /// Start at the volume of this --volume-preset, instead of --volume
+    #[clap(long, value_name = "NAME")]
+    pub preset: Option<String>,
+
+    /// Where to resume after pausing: where it was paused, or where the live stream is now
+    #[clap(long, arg_enum, value_name = "MODE", default_value_t = PauseMode::Timeshift)]
+    pub pause_mode: PauseMode,
+
+    /// What to do on launch when no station is specified
+    #[clap(long, arg_enum, value_name = "ACTION", default_value_t = DefaultAction::Default)]
+    pub default_action: DefaultAction,
+
+    /// Record every metadata message to this file as JSON lines, for replaying with --simulate
+    #[clap(long, value_name = "FILE")]
+    pub dump_metadata: Option<PathBuf>,
+
+    /// Display album art in the terminal on each song change
+    #[clap(long)]
+    pub art: bool,
+
+    /// Download the next song's album art in the last 30 seconds of the current song,
+    /// so it shows up right away when the song changes. Not with --refresh 0
+    #[clap(long, requires = "art")]
+    pub prefetch_art: bool,
+
+    /// Write current song's album art image to this file on each song change
+    #[clap(long, value_name = "PATH")]
+    pub art_file: Option<PathBuf>,
+
+    /// Fade in new streams, and fade out the current one when switching stations,
+    /// over this many milliseconds. 0 to disable
+    #[clap(long, value_name = "MS", default_value_t = 500)]
+    pub fade: u64,
+
+    /// Multiply the volume by this, up to 3, so volume 9 can be louder than the stream itself,
+    /// like on quiet devices. Gain above 1 may clip
+    #[clap(long, value_name = "FACTOR", default_value_t = 1.0)]
+    pub gain: f32,
+
+    /// Stereo balance, between -9 (left) and 9 (right)
+    #[clap(
+        short,
+        long,
+        default_value_t = 0,
+        allow_hyphen_values = true,
+        value_parser = clap::value_parser!(i8).range(-9..=9)
+    )]
+    pub balance: i8,
+
+    /// Coalesce metadata updates arriving within this many milliseconds. 0 to disable
+    #[clap(long, value_name = "MS", default_value_t = 250)]
+    pub metadata_debounce: u64,
+
+    /// Where to send decoded audio. "pcm-stdout" writes raw 16-bit little-endian PCM
+    /// to stdout for piping into other tools, instead of displaying now playing info.
+    /// "null" decodes but discards audio
+    #[clap(long, arg_enum, value_name = "BACKEND", default_value_t = AudioBackendKind::Rodio, conflicts_with = "json")]
+    pub audio_backend: AudioBackendKind,
+
+    /// Play on the audio output device whose name contains this, instead of the default device
+    #[clap(long, value_name = "NAME")]
+    pub device: Option<String>,
+
+    /// Show a desktop notification on each song change
+    #[clap(long)]
+    pub notify: bool,
+
+    /// Write current song's info to this file on each song change, like for streaming overlays
+    #[clap(long, value_name = "PATH")]
+    pub now_playing_file: Option<PathBuf>,
+
+    /// Format of --now-playing-file, with the same placeholders as --format.
+    /// Defaults to --format, or "{artist} - {title}"
+    #[clap(long, value_name = "FORMAT")]
+    pub now_playing_format: Option<String>,
+
+    /// Display each song's info in this format instead of the default layout.
+    /// Supports {title}, {artist}, {album}, {genre}, {listeners}, {elapsed} and {duration}
+    #[clap(long, value_name = "TEMPLATE")]
+    pub format: Option<String>,
+
+    /// Run this shell command on each song change, with song info in CR_TITLE, CR_ARTIST and
+    /// CR_ALBUM environment variables
+    #[clap(long, value_name = "COMMAND")]
+    pub on_song_change: Option<String>,
 
     /// Do not display logo
     #[clap(short, long)]
     pub no_logo: bool,
+
+    /// Display logo even if the terminal is too narrow for it
+    #[clap(long, conflicts_with = "no-logo")]
+    pub force_logo: bool,
+
+    /// Exit when the terminal or the parent process closes.
+    /// Always enabled when stdin is not a terminal, unless --daemon is set
+    #[clap(long)]
+    pub exit_on_hangup: bool,
+
+    /// Print the current song once and exit, without playing anything, like for status bars.
+    /// Prints "{artist} - {title}" unless --format or --json is set
+    #[clap(long, conflicts_with_all = &["tui", "daemon", "simulate", "select-station"])]
+    pub oneshot: bool,
+
+    /// Check DNS, the APIs, the audio stream and the audio device one by one, print what passed
+    /// and failed, and exit. Include its output in bug reports
+    #[clap(long, conflicts_with_all = &["json", "tui", "daemon", "simulate", "oneshot", "url"])]
+    pub doctor: bool,
+
+    /// Only play audio in the background, without any output or keyboard input,
+    /// like under systemd or nohup. Song changes are still reported by --now-playing-file,
+    /// --webhook and other hooks
+    #[clap(long, conflicts_with_all = &["json", "tui", "select-station", "simulate"])]
+    pub daemon: bool,
+
+    /// Print now playing info as JSON lines instead of the progress bar, for scripting
+    #[clap(long)]
+    pub json: bool,
+
+    /// Use a full-screen interface with song history and a station picker,
+    /// instead of the progress bar
+    #[clap(long, conflicts_with_all = &["json", "simulate"])]
+    pub tui: bool,
+
+    /// List audio output devices for --device and exit
+    #[clap(long)]
+    pub list_devices: bool,
+
+    /// List all stations and exit
+    #[clap(long)]
+    pub list_stations: bool,
+
+    /// Add a station to favorites, by its name, ID, or mount shortcode, and exit.
+    /// Press F while playing to switch between favorites, or Alt+1-9 to switch to one of them
+    #[clap(long, value_name = "NAME_OR_SHORTCODE")]
+    pub favorite: Option<String>,
+
+    /// List favorite stations and exit
+    #[clap(long)]
+    pub favorites: bool,
+
+    /// Which listener count to display
+    #[clap(long, arg_enum, value_name = "METRIC", default_value_t = ListenerMetric::Current)]
+    pub listener_metric: ListenerMetric,
+
+    /// Write diagnostic logs to this file
+    #[clap(long, value_name = "PATH")]
+    pub log_file: Option<PathBuf>,
+
+    /// Log what's going on under the hood, like API URLs, the stream played and reconnects,
+    /// for bug reports. Logs go to --log-file if set, or stderr otherwise
+    #[clap(long)]
+    pub verbose: bool,
+
+    /// Print the audio stream's server address and protocol after connecting
+    #[clap(long)]
+    pub show_connection: bool,
+
+    /// Update the progress bar every this many milliseconds. 0 to only update it
+    /// when now playing info changes, like on slow terminals
+    #[clap(long, value_name = "MS", default_value_t = 1000)]
+    pub refresh: u64,
+
+    /// Display the upcoming song and a countdown until it starts
+    #[clap(long)]
+    pub show_next: bool,
+
+    /// Do not send SIGINT to itself on exit to restore terminal state (Unix only).
+    /// Use this if it conflicts with your terminal multiplexer or signal handlers
+    #[clap(long)]
+    pub no_terminal_cleanup: bool,
+
+    /// Do not check for new versions on GitHub, which also leaves the update cache file untouched.
+    /// Also disabled by the CODE_RADIO_NO_UPDATE_CHECK environment variable
+    #[clap(long)]
+    pub no_update_check: bool,
+
+    /// Display current song in terminal title bar
+    #[clap(long)]
+    pub set_title: bool,
+
+    /// Print the most played artists in recent song history and exit
+    #[clap(long, value_name = "N", min_values = 0, default_missing_value = "10")]
+    pub top_artists: Option<usize>,
+}
+
+impl Args {
+    /// Whether to display the welcome message, song info and progress bar on stdout.
+    /// Disabled in daemon mode, or when stdout is used for JSON, raw audio output or the full-screen interface.
+    pub fn is_display_enabled(&self) -> bool {
+        !self.daemon && !self.json && !self.tui && self.audio_backend != AudioBackendKind::PcmStdout
+    }
+
+    /// Whether to check for new versions, unless disabled by `--no-update-check`
+    /// or a non-empty CODE_RADIO_NO_UPDATE_CHECK environment variable.
+    pub fn is_update_check_enabled(&self) -> bool {
+        !self.no_update_check
+            && std::env::var_os("CODE_RADIO_NO_UPDATE_CHECK").is_none_or(|v| v.is_empty())
+    }
+
+    /// `--volume-preset`s in the order they were first given. If a name is given more than once,
+    /// the last one wins, so command line presets override the ones in the config file.
+    pub fn get_volume_presets(&self) -> Vec<VolumePreset> {
+        let mut presets: Vec<VolumePreset> = Vec::new();
+        for preset in &self.volume_preset {
+            match presets.iter_mut().find(|p| p.name == preset.name) {
+                Some(existing) => existing.volume = preset.volume,
+                None => presets.push(preset.clone()),
+            }
+        }
+        presets
+    }
+}
+
+/// A volume between 0 and 9, validated when parsing arguments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Volume(u8);
+
+impl Volume {
+    pub const MAX: u8 = 9;
+
+    pub const fn get(self) -> u8 {
+        self.0
+    }
+}
+
+impl FromStr for Volume {
+    type Err = String;
+
+    /// Parse a volume between 0 and 9, or a percentage which is rounded to the nearest volume step.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.strip_suffix('%') {
+            Some(percentage) => match percentage.trim().parse::<u8>() {
+                Ok(percentage) if percentage <= 100 => Ok(Self(
+                    ((u16::from(percentage) * u16::from(Self::MAX) + 50) / 100) as u8,
+                )),
+                _ => Err("Volume percentage must be between 0% and 100%".to_owned()),
+            },
+            None => match value.trim().parse::<u8>() {
+                Ok(volume) if volume <= Self::MAX => Ok(Self(volume)),
+                _ => Err(format!(
+                    "Volume must be between 0 and {}, or a percentage like \"50%\"",
+                    Self::MAX
+                )),
+            },
+        }
+    }
+}
+
+/// A named volume, like "night=2".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VolumePreset {
+    pub name: String,
+    pub volume: Volume,
+}
+
+impl FromStr for VolumePreset {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (name, volume) = value
+            .split_once('=')
+            .ok_or_else(|| "Volume preset must be like \"night=2\"".to_owned())?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Volume preset name must not be empty".to_owned());
+        }
+        Ok(Self {
+            name: name.to_owned(),
+            volume: volume.parse()?,
+        })
+    }
+}
+
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PauseMode {
+    /// Reconnect to the stream, skipping what was missed while paused
+    Live,
+    /// Continue where it was paused, behind the live stream by the time spent paused
+    Timeshift,
+}
+
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ListenerMetric {
+    /// Concurrent listeners
+    Current,
+    /// Unique listeners
+    Unique,
+    /// Total listeners
+    Total,
+}
+
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DefaultAction {
+    /// Play the station's default mount
+    Default,
+    /// Select a station interactively
+    Select,
+    /// Play the station last selected with --select-station
+    Resume,
+    /// Play the first station added with --favorite, or the default mount if there is none
+    Favorite,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn args_are_valid() {
+        Args::command().debug_assert();
+    }
+
+    #[test]
+    fn balance_out_of_range_is_rejected() {
+        let args = Args::try_parse_from(["code-radio", "--balance", "-9"]).unwrap();
+        assert_eq!(args.balance, -9);
+        assert!(Args::try_parse_from(["code-radio", "--balance", "-10"]).is_err());
+        assert!(Args::try_parse_from(["code-radio", "--balance", "10"]).is_err());
+    }
 }
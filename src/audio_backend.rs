@@ -0,0 +1,288 @@
+use anyhow::{anyhow, Context, Result};
+use clap::ArgEnum;
+use rodio::{
+    cpal::{
+        self,
+        traits::{DeviceTrait, HostTrait},
+    },
+    Device, OutputStream, OutputStreamHandle, Sink, Source,
+};
+use std::{
+    io::{self, Write},
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AudioBackendKind {
+    /// Play on the default audio output device
+    Rodio,
+    /// Write raw PCM to stdout
+    PcmStdout,
+    /// Decode and discard audio
+    Null,
+}
+
+/// Decoded audio, as interleaved 16-bit samples.
+pub type AudioSource = Box<dyn Source<Item = i16> + Send>;
+
+/// Where decoded audio goes.
+///
+/// A backend is created on and only used from the player thread,
+/// because some outputs (like rodio's `OutputStream`) can't be moved between threads.
+pub trait AudioBackend {
+    /// Start playing `source` unpaused, replacing the current one.
+    fn play(&mut self, source: AudioSource) -> Result<()>;
+
+    /// Stop and drop the current source, if any.
+    fn stop(&mut self);
+
+    /// Whether the current source has run out, like when the network stream drops.
+    fn is_finished(&self) -> bool;
+
+    /// Volume between 0 and 1. Takes effect on the current and future sources.
+    fn set_volume(&mut self, volume: f32);
+
+    fn pause(&mut self);
+
+    fn resume(&mut self);
+}
+
+/// `device_name` selects the output device of the rodio backend. See `find_output_device`.
+pub fn create(kind: AudioBackendKind, device_name: Option<&str>) -> Result<Box<dyn AudioBackend>> {
+    Ok(match kind {
+        AudioBackendKind::Rodio => Box::new(RodioBackend::try_new(device_name)?),
+        AudioBackendKind::PcmStdout => Box::new(PcmWriterBackend::new(io::stdout)),
+        AudioBackendKind::Null => Box::new(PcmWriterBackend::new(io::sink)),
+    })
+}
+
+pub struct OutputDevice {
+    pub name: String,
+    pub is_default: bool,
+}
+
+/// List the output devices of the default host, without opening any of them.
+pub fn list_output_devices() -> Result<Vec<OutputDevice>> {
+    let host = cpal::default_host();
+    let default_device_name = host.default_output_device().and_then(|d| d.name().ok());
+
+    Ok(host
+        .output_devices()
+        .context("Failed to list audio devices")?
+        .filter_map(|device| device.name().ok())
+        .map(|name| OutputDevice {
+            is_default: default_device_name.as_ref() == Some(&name),
+            name,
+        })
+        .collect())
+}
+
+/// Find the output device whose name contains `name`, ignoring case.
+/// If none matches, the error lists all available devices.
+fn find_output_device(name: &str) -> Result<Device> {
+    let devices: Vec<Device> = cpal::default_host()
+        .output_devices()
+        .context("Failed to list audio devices")?
+        .collect();
+
+    let name_lowercase = name.to_lowercase();
+    let is_match = |device: &Device| {
+        device
+            .name()
+            .is_ok_and(|device_name| device_name.to_lowercase().contains(&name_lowercase))
+    };
+    if let Some(index) = devices.iter().position(is_match) {
+        return Ok(devices.into_iter().nth(index).unwrap());
+    }
+
+    let device_names: Vec<String> = devices.iter().filter_map(|d| d.name().ok()).collect();
+    Err(anyhow!(
+        "No audio device matches \"{}\". Available devices:\n{}",
+        name,
+        device_names.join("\n")
+    ))
+}
+
+/// Play audio on an output device, or the default one.
+struct RodioBackend {
+    _stream: OutputStream, // Audio stops if this is dropped
+    stream_handle: OutputStreamHandle,
+    sink: Option<Sink>,
+    volume: f32,
+}
+
+impl RodioBackend {
+    fn try_new(device_name: Option<&str>) -> Result<Self> {
+        let (stream, stream_handle) = match device_name {
+            Some(device_name) => OutputStream::try_from_device(&find_output_device(device_name)?),
+            None => OutputStream::try_default(),
+        }
+        .context("Audio device initialization failed")?;
+
+        Ok(Self {
+            _stream: stream,
+            stream_handle,
+            sink: None,
+            volume: 1.0,
+        })
+    }
+}
+
+impl AudioBackend for RodioBackend {
+    fn play(&mut self, source: AudioSource) -> Result<()> {
+        self.stop();
+
+        let sink = Sink::try_new(&self.stream_handle)?;
+        sink.set_volume(self.volume);
+        sink.append(source);
+        self.sink = Some(sink);
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        self.sink = None;
+    }
+
+    fn is_finished(&self) -> bool {
+        self.sink.as_ref().is_none_or(Sink::empty)
+    }
+
+    fn set_volume(&mut self, volume: f32) {
+        self.volume = volume;
+        if let Some(sink) = &self.sink {
+            sink.set_volume(volume);
+        }
+    }
+
+    fn pause(&mut self) {
+        if let Some(sink) = &self.sink {
+            sink.pause();
+        }
+    }
+
+    fn resume(&mut self) {
+        if let Some(sink) = &self.sink {
+            sink.play();
+        }
+    }
+}
+
+/// Write raw PCM (interleaved signed 16-bit little-endian samples) to a writer,
+/// like stdout for piping into other tools, or `io::sink()` to discard audio.
+///
+/// Each source is drained on its own thread.
+/// The network stream is live, so it paces the writing.
+struct PcmWriterBackend<F> {
+    create_writer: F,
+    volume: Arc<AtomicU32>, // f32 bits
+    current: Option<PcmWriterControl>,
+}
+
+struct PcmWriterControl {
+    paused: Arc<AtomicBool>,
+    stopped: Arc<AtomicBool>,
+    finished: Arc<AtomicBool>,
+}
+
+impl<F, W> PcmWriterBackend<F>
+where
+    F: Fn() -> W,
+    W: Write + Send + 'static,
+{
+    fn new(create_writer: F) -> Self {
+        Self {
+            create_writer,
+            volume: Arc::new(AtomicU32::new(1_f32.to_bits())),
+            current: None,
+        }
+    }
+}
+
+impl<F, W> AudioBackend for PcmWriterBackend<F>
+where
+    F: Fn() -> W,
+    W: Write + Send + 'static,
+{
+    fn play(&mut self, source: AudioSource) -> Result<()> {
+        self.stop();
+
+        let control = PcmWriterControl {
+            paused: Arc::new(AtomicBool::new(false)),
+            stopped: Arc::new(AtomicBool::new(false)),
+            finished: Arc::new(AtomicBool::new(false)),
+        };
+
+        let writer = io::BufWriter::new((self.create_writer)());
+        let volume = self.volume.clone();
+        let paused = control.paused.clone();
+        let stopped = control.stopped.clone();
+        let finished = control.finished.clone();
+        thread::spawn(move || {
+            if let Err(e) = write_pcm(source, writer, &volume, &paused, &stopped) {
+                log::error!("Failed to write PCM output: {:#}", e);
+            }
+            finished.store(true, Ordering::Relaxed);
+        });
+
+        self.current = Some(control);
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        if let Some(control) = self.current.take() {
+            control.stopped.store(true, Ordering::Relaxed);
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.current
+            .as_ref()
+            .is_none_or(|control| control.finished.load(Ordering::Relaxed))
+    }
+
+    fn set_volume(&mut self, volume: f32) {
+        self.volume.store(volume.to_bits(), Ordering::Relaxed);
+    }
+
+    fn pause(&mut self) {
+        if let Some(control) = &self.current {
+            control.paused.store(true, Ordering::Relaxed);
+        }
+    }
+
+    fn resume(&mut self) {
+        if let Some(control) = &self.current {
+            control.paused.store(false, Ordering::Relaxed);
+        }
+    }
+}
+
+fn write_pcm(
+    source: AudioSource,
+    mut writer: impl Write,
+    volume: &AtomicU32,
+    paused: &AtomicBool,
+    stopped: &AtomicBool,
+) -> io::Result<()> {
+    const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+    for sample in source {
+        while paused.load(Ordering::Relaxed) && !stopped.load(Ordering::Relaxed) {
+            thread::sleep(PAUSE_POLL_INTERVAL);
+        }
+        if stopped.load(Ordering::Relaxed) {
+            break;
+        }
+
+        let volume = f32::from_bits(volume.load(Ordering::Relaxed));
+        let sample = (f32::from(sample) * volume) as i16;
+        writer.write_all(&sample.to_le_bytes())?;
+    }
+
+    writer.flush()
+}
@@ -0,0 +1,23 @@
+use anyhow::{anyhow, Result};
+use arboard::Clipboard;
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+
+/// Kept alive until exit, because on Linux the copied text is served by its owner process,
+/// and would be lost if the clipboard is dropped right after copying.
+///
+/// `None` if no clipboard is available, like on headless Linux.
+static CLIPBOARD: Lazy<Mutex<Option<Clipboard>>> = Lazy::new(|| {
+    let clipboard = Clipboard::new();
+    if let Err(e) = &clipboard {
+        log::warn!("Clipboard is not available: {}", e);
+    }
+    Mutex::new(clipboard.ok())
+});
+
+pub fn copy_text(text: String) -> Result<()> {
+    match CLIPBOARD.lock().unwrap().as_mut() {
+        Some(clipboard) => Ok(clipboard.set_text(text)?),
+        None => Err(anyhow!("Clipboard is not available")),
+    }
+}
@@ -0,0 +1,74 @@
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+#[cfg(test)]
+use std::{sync::Mutex, time::Duration};
+
+/// Source of time for progress/elapsed calculations, so they can be driven by a fake clock.
+pub trait Clock {
+    /// Monotonic time, for measuring how much time has passed.
+    fn now(&self) -> Instant;
+
+    /// Seconds since unix epoch, for comparing with timestamps from Code Radio's API like `played_at`.
+    fn unix_timestamp(&self) -> i64;
+}
+
+/// The real wall clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn unix_timestamp(&self) -> i64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default()
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> Instant {
+        (**self).now()
+    }
+
+    fn unix_timestamp(&self) -> i64 {
+        (**self).unix_timestamp()
+    }
+}
+
+/// A clock which only moves forward when `advance` is called, for testing time-related logic deterministically.
+#[cfg(test)]
+#[derive(Debug)]
+pub struct ManualClock {
+    start: Instant,
+    start_unix_timestamp: i64,
+    advanced: Mutex<Duration>,
+}
+
+#[cfg(test)]
+impl ManualClock {
+    pub fn new(start_unix_timestamp: i64) -> Self {
+        Self {
+            start: Instant::now(),
+            start_unix_timestamp,
+            advanced: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.advanced.lock().unwrap() += duration;
+    }
+}
+
+#[cfg(test)]
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.start + *self.advanced.lock().unwrap()
+    }
+
+    fn unix_timestamp(&self) -> i64 {
+        self.start_unix_timestamp + self.advanced.lock().unwrap().as_secs() as i64
+    }
+}
@@ -1,56 +1,231 @@
-use crate::models::{
-    code_radio::{CodeRadioMessage, Remote},
-    server_sent_events::{Np, SeverSentEventsChannelMessage},
+use crate::{
+    http_client,
+    models::{
+        code_radio::{CodeRadioMessage, Remote},
+        server_sent_events::{Np, SeverSentEventsChannelMessage},
+    },
+    sse_client, utils,
 };
-use anyhow::{anyhow, Result};
-use eventsource_client::{Client, SSE::Event};
-use futures_util::{FutureExt, Stream, StreamExt, TryStreamExt};
-use std::{pin::Pin, time::Duration};
+use anyhow::{anyhow, Context, Result};
+use futures_util::{Stream, StreamExt, TryStreamExt};
+use once_cell::sync::OnceCell;
+use reqwest::Url;
+use std::{
+    pin::Pin,
+    sync::atomic::{AtomicU32, Ordering},
+    time::Duration,
+};
+use tokio::{sync::mpsc, time::Instant};
+
+pub const DEFAULT_API_URL: &str = "https://coderadio-admin-v2.freecodecamp.org";
+pub const DEFAULT_STATION_SHORTCODE: &str = "coderadio";
+
+static API_ENDPOINTS: OnceCell<ApiEndpoints> = OnceCell::new();
+/// Server-Sent Events messages in a row which failed to parse. Reset by each message parsed.
+static CONSECUTIVE_PARSE_FAILURES: AtomicU32 = AtomicU32::new(0);
+
+struct ApiEndpoints {
+    rest_api_url: Url,
+    server_sent_events_api_url: Url,
+}
+
+impl ApiEndpoints {
+    /// Build the endpoints of an AzuraCast instance at `api_url` for the station `station_shortcode`.
+    fn new(api_url: &str, station_shortcode: &str) -> Result<Self> {
+        let api_url = api_url.trim_end_matches('/');
+
+        let rest_api_url = Url::parse(&format!(
+            "{api_url}/api/nowplaying_static/{station_shortcode}.json"
+        ))
+        .with_context(|| format!("Invalid API URL \"{}\"", api_url))?;
+
+        let subscription = serde_json::json!({
+            "subs": { format!("station:{station_shortcode}"): {} }
+        });
+        let server_sent_events_api_url = Url::parse_with_params(
+            &format!("{api_url}/api/live/nowplaying/sse"),
+            [("cf_connect", subscription.to_string())],
+        )
+        .with_context(|| format!("Invalid API URL \"{}\"", api_url))?;
+
+        Ok(Self {
+            rest_api_url,
+            server_sent_events_api_url,
+        })
+    }
+}
+
+/// Use the AzuraCast instance at `api_url` and its station `station_shortcode`
+/// instead of freeCodeCamp's Code Radio. Call this before any other function in this module.
+pub fn set_api_url(api_url: &str, station_shortcode: &str) -> Result<()> {
+    let api_endpoints = ApiEndpoints::new(api_url, station_shortcode)?;
+    log::debug!("REST API URL: {}", api_endpoints.rest_api_url);
+    log::debug!(
+        "Server-Sent Events API URL: {}",
+        api_endpoints.server_sent_events_api_url
+    );
+    API_ENDPOINTS
+        .set(api_endpoints)
+        .map_err(|_| anyhow!("API URL has already been set"))
+}
+
+/// How long to wait before reconnecting when the Server-Sent Events stream drops:
+/// `initial_delay` at first, doubling on each failed attempt up to `max_delay`, with random jitter.
+/// Call this before `get_message_stream`.
+pub fn set_reconnect_delays(initial_delay: Duration, max_delay: Duration) {
+    sse_client::set_reconnect_delays(initial_delay, max_delay);
+}
+
+fn api_endpoints() -> &'static ApiEndpoints {
+    API_ENDPOINTS.get_or_init(|| {
+        ApiEndpoints::new(DEFAULT_API_URL, DEFAULT_STATION_SHORTCODE)
+            .expect("Default API URL should be valid")
+    })
+}
+
+/// URL of the REST API, as set by `set_api_url`.
+pub fn rest_api_url() -> &'static Url {
+    &api_endpoints().rest_api_url
+}
 
-const REST_API_URL: &str =
-    "https://coderadio-admin-v2.freecodecamp.org/api/nowplaying_static/coderadio.json";
-const SERVER_SENT_EVENTS_API_URL: &str =
-    "https://coderadio-admin-v2.freecodecamp.org/api/live/nowplaying/sse?cf_connect=%7B%22subs%22%3A%7B%22station%3Acoderadio%22%3A%7B%7D%7D%7D";
+/// URL of the Server-Sent Events API, as set by `set_api_url`.
+pub fn server_sent_events_api_url() -> &'static Url {
+    &api_endpoints().server_sent_events_api_url
+}
 
 /// Get a `CodeRadioMessage` with REST API.
 pub async fn get_message() -> Result<CodeRadioMessage> {
-    let message: CodeRadioMessage = reqwest::get(REST_API_URL).await?.json().await?;
+    log::debug!("Getting now playing info from REST API");
+    let message: CodeRadioMessage = http_client::client()
+        .get(api_endpoints().rest_api_url.clone())
+        .timeout(http_client::request_timeout())
+        .send()
+        .await
+        .map_err(http_client::explain_timeout)?
+        .json()
+        .await
+        .map_err(http_client::explain_timeout)?;
     Ok(message)
 }
 
 /// Get a `CodeRadioMessage` stream with Server-Sent Events API.
-pub fn get_message_stream() -> Pin<Box<dyn Stream<Item = Result<CodeRadioMessage>>>> {
-    let sse_client = eventsource_client::ClientBuilder::for_url(SERVER_SENT_EVENTS_API_URL)
-        .unwrap()
-        .reconnect(
-            eventsource_client::ReconnectOptions::reconnect(true)
-                .retry_initial(false)
-                .delay(Duration::from_secs(1))
-                .backoff_factor(2)
-                .delay_max(Duration::from_secs(20))
-                .build(),
-        )
-        .build();
+pub fn get_message_stream() -> Pin<Box<dyn Stream<Item = Result<CodeRadioMessage>> + Send>> {
+    let sse_stream = sse_client::connect(api_endpoints().server_sent_events_api_url.clone());
+
+    let sse_message_stream = sse_stream.try_filter_map(|event| async move {
+        match parse_server_sent_events_data(&event.data) {
+            Ok(Some(message)) => {
+                CONSECUTIVE_PARSE_FAILURES.store(0, Ordering::Relaxed);
+                Ok(Some(message))
+            }
+            Ok(None) => Ok(None),
+            Err(e) => {
+                CONSECUTIVE_PARSE_FAILURES.fetch_add(1, Ordering::Relaxed);
+                // Only the start of the data, since a whole message is several kilobytes
+                log::warn!(
+                    "Failed to parse Server-Sent Events message: {:#}. Data: {:.200}",
+                    e,
+                    event.data
+                );
+                Ok(None)
+            }
+        }
+    });
+
+    Box::pin(sse_message_stream)
+}
+
+/// How many Server-Sent Events messages in a row failed to parse, like after the server's message format changed,
+/// which would otherwise leave now playing info stale without any sign.
+/// Heartbeats, like pings and the connect event, are not counted.
+pub fn consecutive_parse_failures() -> u32 {
+    CONSECUTIVE_PARSE_FAILURES.load(Ordering::Relaxed)
+}
+
+/// Parse the data of a Server-Sent Event.
+/// `Ok(None)` for heartbeats, like `{}` pings, the connect event and time updates of the "global:time" channel.
+fn parse_server_sent_events_data(data: &str) -> Result<Option<CodeRadioMessage>> {
+    if data.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let value: serde_json::Value = serde_json::from_str(data).context("Invalid JSON")?;
+    let channel = value.get("channel").and_then(|channel| channel.as_str());
+    let is_station_message =
+        value.get("pub").is_some() && channel.is_none_or(|c| c.starts_with("station:"));
+    if !is_station_message {
+        return Ok(None);
+    }
 
-    let mut sse_stream = sse_client.stream();
+    let message: SeverSentEventsChannelMessage<Np> =
+        serde_json::from_value(value).context("Unexpected now playing info format")?;
+    Ok(Some(message.r#pub.data.np))
+}
+
+/// Like `get_message_stream`, but errors are only logged and the stream never ends:
+/// If the Server-Sent Events stream closes, poll the REST API instead,
+/// and periodically try to connect to the Server-Sent Events stream again.
+///
+/// Only ends if it runs out of reconnection attempts, set by `http_client::NetworkOptions::max_retries`.
+pub fn get_message_stream_with_fallback() -> Pin<Box<dyn Stream<Item = CodeRadioMessage> + Send>> {
+    const REST_API_POLLING_INTERVAL: Duration = Duration::from_secs(5);
+    const SERVER_SENT_EVENTS_RETRY_INTERVAL: Duration = Duration::from_secs(60);
 
-    sse_stream.next().now_or_never(); // Poll once to start connecting immediately
+    let (sender, receiver) = mpsc::unbounded_channel();
 
-    let sse_message_stream = sse_stream
-        .try_filter_map(|response| async move {
-            if let Event(event) = response {
-                if let Ok(message) =
-                    serde_json::from_str::<SeverSentEventsChannelMessage<Np>>(&event.data)
-                {
-                    return Ok(Some(message.r#pub.data.np));
+    tokio::spawn(async move {
+        let mut message_stream = get_message_stream();
+
+        loop {
+            while let Some(message) = message_stream.next().await {
+                match message {
+                    Ok(message) => {
+                        if sender.send(message).is_err() {
+                            return; // Stream dropped
+                        }
+                    }
+                    // The Server-Sent Events client reconnects by itself
+                    Err(e) => log::warn!("Server-Sent Events error: {:#}", e),
                 }
             }
-            Ok(None)
-        })
-        .map_err(|error| anyhow!("Server-Sent Events Error: {:#?}", error))
-        .into_stream();
 
-    Box::pin(sse_message_stream)
+            if http_client::is_out_of_retries() {
+                return;
+            }
+            log::warn!("Server-Sent Events stream closed, polling REST API instead");
+
+            let retry_server_sent_events_at = Instant::now() + SERVER_SENT_EVENTS_RETRY_INTERVAL;
+            let mut polling_interval = tokio::time::interval(REST_API_POLLING_INTERVAL);
+            while Instant::now() < retry_server_sent_events_at {
+                polling_interval.tick().await;
+                match get_message().await {
+                    Ok(message) => {
+                        http_client::reset_retries();
+                        if sender.send(message).is_err() {
+                            return; // Stream dropped
+                        }
+                    }
+                    Err(e) => {
+                        log::warn!("Failed to poll REST API: {:#}", e);
+                        if !http_client::try_retry() {
+                            return;
+                        }
+                    }
+                }
+            }
+
+            log::info!("Reconnecting to Server-Sent Events stream");
+            message_stream = get_message_stream();
+        }
+    });
+
+    Box::pin(futures_util::stream::unfold(
+        receiver,
+        |mut receiver| async move {
+            let message = receiver.recv().await?;
+            Some((message, receiver))
+        },
+    ))
 }
 
 /// Get all stations with REST API.
@@ -72,3 +247,56 @@ pub fn get_stations_from_message(message: &CodeRadioMessage) -> Vec<Remote> {
     stations.sort_by_key(|s| s.id);
     stations
 }
+
+/// Find the station whose bitrate is closest to `bitrate` in kbps.
+/// On a tie, prefer the higher bitrate.
+pub fn find_station_by_bitrate(stations: &[Remote], bitrate: i64) -> Option<&Remote> {
+    stations
+        .iter()
+        .min_by_key(|s| ((s.bitrate - bitrate).abs(), std::cmp::Reverse(s.bitrate)))
+}
+
+/// Find a station by its ID, name, or mount shortcode (the last segment of its URL, like "low.mp3" or "low").
+/// Names and shortcodes are matched case-insensitively.
+///
+/// If no station matches, the error lists the stations with the closest names.
+pub fn find_station<'a>(stations: &'a [Remote], query: &str) -> Result<&'a Remote> {
+    let query = query.trim();
+    let query_lowercase = query.to_lowercase();
+
+    let matches_query = |station: &Remote| {
+        if query.parse::<i64>() == Ok(station.id) {
+            return true;
+        }
+        if station.name.to_lowercase() == query_lowercase {
+            return true;
+        }
+        let shortcode = station
+            .url
+            .rsplit('/')
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        shortcode == query_lowercase
+            || shortcode.split('.').next() == Some(query_lowercase.as_str())
+    };
+
+    if let Some(station) = stations.iter().find(|s| matches_query(s)) {
+        return Ok(station);
+    }
+
+    let mut closest_stations: Vec<&Remote> = stations.iter().collect();
+    closest_stations
+        .sort_by_key(|s| utils::get_edit_distance(&s.name.to_lowercase(), &query_lowercase));
+    let closest_station_names: Vec<String> = closest_stations
+        .iter()
+        .take(3)
+        .map(|s| format!("  {} (ID: {})", s.name, s.id))
+        .collect();
+
+    Err(anyhow!(
+        "Station \"{}\" not found. Closest matches:\n{}",
+        query,
+        closest_station_names.join("\n")
+    ))
+}
@@ -0,0 +1,153 @@
+//! A TOML config file for default options, so users don't have to type the same flags every time.
+//!
+//! Each key is the name of a command line option, and its value is the option's value:
+//!
+//! ```toml
+//! volume = 5
+//! station = "low.mp3"
+//! no_logo = true
+//!
+//! [volume_preset]
+//! night = 2
+//! party = 9
+//! ```
+//!
+//! Tables are for options which take `NAME=VALUE`, like `--volume-preset night=2`.
+//!
+//! Options in the config file are put before the ones from the command line,
+//! so explicit command line flags override the config file. Options which conflict with
+//! command line flags, like `station` with `--select-station`, are ignored.
+
+use anyhow::{anyhow, Context, Result};
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, ValueSource};
+use std::{ffi::OsString, path::PathBuf};
+use toml::{Table, Value};
+
+use crate::{app_dirs, args::Args};
+
+const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Like `~/.config/code-radio/config.toml` on Linux.
+pub fn get_default_config_file_path() -> Option<PathBuf> {
+    app_dirs::get_project_dirs().map(|dirs| dirs.config_dir().join(CONFIG_FILE_NAME))
+}
+
+/// Parse command line arguments, merged with the options in the config file.
+pub fn parse_args() -> Result<Args> {
+    let command_line_matches = Args::command().get_matches();
+    let args = Args::from_arg_matches(&command_line_matches).unwrap_or_else(|e| e.exit());
+    if args.no_config {
+        return Ok(args);
+    }
+
+    let config_file_content = match &args.config {
+        Some(path) => std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?,
+        None => match get_default_config_file_path() {
+            Some(path) if path.exists() => std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read config file {}", path.display()))?,
+            _ => return Ok(args),
+        },
+    };
+
+    let mut config: Table = config_file_content
+        .parse()
+        .context("Failed to parse config file")?;
+    remove_conflicting_options(&mut config, &command_line_matches);
+
+    let config_args = convert_config_to_args(config)?;
+    if config_args.is_empty() {
+        return Ok(args);
+    }
+
+    let mut command_line_args = std::env::args_os();
+    let executable = command_line_args.next().unwrap_or_default();
+    let merged_args = std::iter::once(executable)
+        .chain(config_args)
+        .chain(command_line_args);
+
+    Ok(Args::try_parse_from(merged_args).unwrap_or_else(|e| e.exit()))
+}
+
+/// Remove options which conflict with flags given on the command line,
+/// so `station = "..."` in the config file doesn't make `--select-station` an error.
+fn remove_conflicting_options(config: &mut Table, command_line_matches: &ArgMatches) {
+    let command = Args::command();
+    config.retain(|key, _| {
+        let long = key.replace('_', "-");
+        let Some(arg) = command
+            .get_arguments()
+            .find(|arg| arg.get_long() == Some(long.as_str()))
+        else {
+            return true;
+        };
+
+        !command
+            .get_arg_conflicts_with(arg)
+            .iter()
+            .any(|conflicting_arg| {
+                command_line_matches.value_source(conflicting_arg.get_id())
+                    == Some(ValueSource::CommandLine)
+            })
+    });
+}
+
+/// Convert each `key = value` to command line arguments, like `--key value`.
+fn convert_config_to_args(config: Table) -> Result<Vec<OsString>> {
+    let mut args = Vec::new();
+    for (key, value) in config {
+        let flag = format!("--{}", key.replace('_', "-"));
+        match value {
+            Value::Boolean(true) => args.push(flag.into()),
+            Value::Boolean(false) => {}
+            Value::String(value) => args.extend([flag.into(), value.into()]),
+            Value::Integer(value) => args.extend([flag.into(), value.to_string().into()]),
+            Value::Float(value) => args.extend([flag.into(), value.to_string().into()]),
+            // Repeatable options, like `watch = ["Lofi", "Chillhop"]`
+            Value::Array(values) => {
+                for value in values {
+                    let value = match value {
+                        Value::String(value) => value,
+                        Value::Integer(value) => value.to_string(),
+                        Value::Float(value) => value.to_string(),
+                        _ => {
+                            return Err(anyhow!(
+                                "Unsupported value of \"{}\" in config file: {}",
+                                key,
+                                value
+                            ))
+                        }
+                    };
+                    args.extend([flag.clone().into(), value.into()]);
+                }
+            }
+            // Named options, like `[volume_preset]` with `night = 2`
+            Value::Table(values) => {
+                for (name, value) in values {
+                    let value = match value {
+                        Value::String(value) => value,
+                        Value::Integer(value) => value.to_string(),
+                        _ => {
+                            return Err(anyhow!(
+                                "Unsupported value of \"{}.{}\" in config file: {}",
+                                key,
+                                name,
+                                value
+                            ))
+                        }
+                    };
+                    args.extend([flag.clone().into(), format!("{}={}", name, value).into()]);
+                }
+            }
+            _ => {
+                return Err(anyhow!(
+                    "Unsupported value of \"{}\" in config file: {}",
+                    key,
+                    value
+                ))
+            }
+        }
+    }
+
+    Ok(args)
+}
@@ -0,0 +1,174 @@
+//! `--doctor`: Check each step of playing one by one, like DNS, the APIs, the stream and the audio device,
+//! so "it doesn't work" turns into which step doesn't.
+
+use anyhow::{anyhow, Context, Result};
+use code_radio_cli::{
+    code_radio_api, http_client,
+    player::{Player, PlayerOptions},
+};
+use colored::Colorize;
+use futures_util::StreamExt;
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+
+/// The Server-Sent Events stream has no timeout by itself, since it's meant to stay open.
+const SERVER_SENT_EVENTS_TIMEOUT: Duration = Duration::from_secs(15);
+
+pub async fn run(player_options: PlayerOptions) -> Result<()> {
+    println!("Checking whether Code Radio can play...");
+    println!();
+
+    let mut checks = Checks::default();
+    let rest_api_url = code_radio_api::rest_api_url();
+
+    checks
+        .run("DNS", async {
+            let host = rest_api_url.host_str().context("API URL has no host")?;
+            let port = rest_api_url.port_or_known_default().unwrap_or(443);
+            let addresses: Vec<String> = tokio::net::lookup_host((host, port))
+                .await
+                .with_context(|| format!("Failed to resolve {}", host))?
+                .map(|address| address.ip().to_string())
+                .collect();
+            Ok(((), format!("{} is {}", host, addresses.join(", "))))
+        })
+        .await;
+
+    let message = checks
+        .run("REST API", async {
+            let message = code_radio_api::get_message().await?;
+            let details = format!("{} ({})", rest_api_url, message.station.name);
+            Ok((message, details))
+        })
+        .await;
+
+    checks
+        .run("Server-Sent Events API", async {
+            let mut message_stream = code_radio_api::get_message_stream();
+            let message = tokio::time::timeout(SERVER_SENT_EVENTS_TIMEOUT, message_stream.next())
+                .await
+                .map_err(|_| {
+                    anyhow!(
+                        "No now playing info within {} seconds",
+                        SERVER_SENT_EVENTS_TIMEOUT.as_secs()
+                    )
+                })?
+                .context("Stream closed without any now playing info")??;
+            let details = format!(
+                "{} (Now playing: {})",
+                code_radio_api::server_sent_events_api_url()
+                    .host_str()
+                    .unwrap_or_default(),
+                message.now_playing.song.title
+            );
+            Ok(((), details))
+        })
+        .await;
+
+    match &message {
+        Some(message) => {
+            let listen_url = &message.station.listen_url;
+            checks
+                .run("Audio stream", async {
+                    let response = http_client::client()
+                        .get(listen_url)
+                        .send()
+                        .await
+                        .map_err(http_client::explain_timeout)?
+                        .error_for_status()?;
+                    let content_type = response
+                        .headers()
+                        .get(reqwest::header::CONTENT_TYPE)
+                        .and_then(|v| v.to_str().ok())
+                        .unwrap_or("unknown content type")
+                        .to_owned();
+                    // Dropping the response closes the stream
+                    Ok(((), format!("{} ({})", listen_url, content_type)))
+                })
+                .await;
+        }
+        None => checks.skip("Audio stream", "Its URL comes from the REST API"),
+    }
+
+    let audio_backend = player_options.audio_backend;
+    let device = player_options.device.clone();
+    checks
+        .run("Audio device", async move {
+            // Creating a player blocks for a while, like on first run
+            tokio::task::spawn_blocking(move || Player::try_new(player_options).map(drop))
+                .await??;
+            let device = device.as_deref().unwrap_or("default device");
+            Ok(((), format!("{:?}, {}", audio_backend, device)))
+        })
+        .await;
+
+    println!();
+    if checks.failed > 0 {
+        return Err(anyhow!(
+            "{} of {} checks failed",
+            checks.failed,
+            checks.total
+        ));
+    }
+    println!("{}", "All checks passed".bright_green());
+    Ok(())
+}
+
+#[derive(Default)]
+struct Checks {
+    total: usize,
+    failed: usize,
+}
+
+impl Checks {
+    /// Run `check`, and print whether it passed, how long it took,
+    /// and the details it returns, or its error.
+    async fn run<T>(
+        &mut self,
+        name: &str,
+        check: impl Future<Output = Result<(T, String)>>,
+    ) -> Option<T> {
+        self.total += 1;
+        let start = Instant::now();
+        let result = check.await;
+        let elapsed_millis = start.elapsed().as_millis();
+
+        match result {
+            Ok((value, details)) => {
+                println!(
+                    "{}  {:<24}{:>6}ms  {}",
+                    "PASS".bright_green(),
+                    name,
+                    elapsed_millis,
+                    details
+                );
+                Some(value)
+            }
+            Err(e) => {
+                self.failed += 1;
+                println!(
+                    "{}  {:<24}{:>6}ms  {:#}",
+                    "FAIL".bright_red(),
+                    name,
+                    elapsed_millis,
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    /// A check which can't run, since another one it depends on failed.
+    fn skip(&mut self, name: &str, reason: &str) {
+        self.total += 1;
+        println!(
+            "{}  {:<24}{:>8}  {}",
+            "SKIP".bright_yellow(),
+            name,
+            "",
+            reason
+        );
+    }
+}
@@ -0,0 +1,52 @@
+//! Distinct process exit codes, so scripts and supervisors can tell failures apart.
+
+use anyhow::Result;
+use std::fmt::{self, Display};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    /// Network or API errors, and anything else not listed below
+    Network = 1,
+    /// Invalid command line arguments or config file. Same as clap's usage errors.
+    InvalidArguments = 2,
+    AudioDevice = 3,
+}
+
+/// An error which exits the process with `code`.
+/// Displays the same as the wrapped error, including its causes.
+#[derive(Debug)]
+struct ExitCodeError {
+    code: ExitCode,
+    error: anyhow::Error,
+}
+
+impl Display for ExitCodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#}", self.error)
+    }
+}
+
+impl std::error::Error for ExitCodeError {}
+
+pub trait WithExitCode<T> {
+    /// Exit with `code` if this error reaches `main`.
+    fn exit_code(self, code: ExitCode) -> Result<T>;
+}
+
+impl<T> WithExitCode<T> for Result<T> {
+    fn exit_code(self, code: ExitCode) -> Result<T> {
+        self.map_err(|error| ExitCodeError { code, error }.into())
+    }
+}
+
+/// The exit code attached to `error` or any of its causes, or `ExitCode::Network` if there is none.
+pub fn get_exit_code(error: &anyhow::Error) -> ExitCode {
+    error
+        .chain()
+        .find_map(|e| e.downcast_ref::<ExitCodeError>())
+        .map_or(ExitCode::Network, |e| e.code)
+}
+
+pub fn exit(code: ExitCode) -> ! {
+    std::process::exit(code as i32)
+}
@@ -0,0 +1,41 @@
+//! Favorite stations, remembered in the state file by ID, to flip through while playing.
+
+use anyhow::Result;
+
+use crate::{models::code_radio::Remote, persisted_state};
+
+pub async fn load_ids() -> Vec<i64> {
+    persisted_state::load().await.favorite_station_ids
+}
+
+/// Add a station to the end of the favorites. Returns `false` if it's already a favorite.
+pub async fn add(station_id: i64) -> Result<bool> {
+    let mut added = false;
+    persisted_state::update(|state| {
+        if !state.favorite_station_ids.contains(&station_id) {
+            state.favorite_station_ids.push(station_id);
+            added = true;
+        }
+    })
+    .await?;
+    Ok(added)
+}
+
+/// Find the favorite stations in `stations`, in the order they were added.
+/// Favorites which no longer exist, like removed mounts, are skipped.
+pub fn resolve<'a>(favorite_ids: &[i64], stations: &'a [Remote]) -> Vec<&'a Remote> {
+    favorite_ids
+        .iter()
+        .filter_map(|id| stations.iter().find(|station| station.id == *id))
+        .collect()
+}
+
+/// The favorite after the one playing `current_url`, wrapping around.
+/// The first favorite if the current station is not a favorite.
+pub fn get_next<'a>(favorites: &[&'a Remote], current_url: Option<&str>) -> Option<&'a Remote> {
+    let next_index = favorites
+        .iter()
+        .position(|station| Some(station.url.as_str()) == current_url)
+        .map_or(0, |index| (index + 1) % favorites.len());
+    favorites.get(next_index).copied()
+}
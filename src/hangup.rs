@@ -0,0 +1,82 @@
+#[cfg(unix)]
+use std::time::Duration;
+
+#[cfg(unix)]
+const PARENT_PROCESS_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Resolves when the controlling terminal hangs up (SIGHUP), or the parent process exits.
+///
+/// When the parent process exits, this process is re-parented (usually to init), so its parent PID changes.
+///
+/// On non-Unix OS this never resolves.
+pub async fn wait_for_hangup() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let initial_parent_pid = unsafe { libc::getppid() };
+        let mut interval = tokio::time::interval(PARENT_PROCESS_CHECK_INTERVAL);
+
+        let mut hangup_signal = match signal(SignalKind::hangup()) {
+            Ok(hangup_signal) => hangup_signal,
+            Err(e) => {
+                log::warn!("Failed to listen to SIGHUP: {}", e);
+                return std::future::pending().await;
+            }
+        };
+
+        loop {
+            tokio::select! {
+                _ = hangup_signal.recv() => return,
+                _ = interval.tick() => {
+                    if unsafe { libc::getppid() } != initial_parent_pid {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    std::future::pending::<()>().await;
+}
+
+/// Resolves when the process is asked to terminate (SIGTERM), or the controlling terminal hangs up (SIGHUP),
+/// so it can shut down cleanly instead of being killed with the terminal left in raw mode.
+///
+/// With `ignore_hangup`, SIGHUP is swallowed instead, like under `nohup`.
+///
+/// On non-Unix OS this never resolves.
+#[cfg_attr(not(unix), allow(unused_variables))]
+pub async fn wait_for_termination_signal(ignore_hangup: bool) {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let (mut terminate_signal, mut hangup_signal) = match (
+            signal(SignalKind::terminate()),
+            signal(SignalKind::hangup()),
+        ) {
+            (Ok(terminate_signal), Ok(hangup_signal)) => (terminate_signal, hangup_signal),
+            (Err(e), _) | (_, Err(e)) => {
+                log::warn!("Failed to listen to SIGTERM and SIGHUP: {}", e);
+                return std::future::pending().await;
+            }
+        };
+
+        loop {
+            tokio::select! {
+                _ = terminate_signal.recv() => return,
+                _ = hangup_signal.recv() => {
+                    if !ignore_hangup {
+                        return;
+                    }
+                    log::info!("Ignoring SIGHUP in daemon mode");
+                }
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    std::future::pending::<()>().await;
+}
@@ -0,0 +1,137 @@
+//! Shared HTTP clients, so network settings like proxy apply to every request.
+
+use anyhow::{anyhow, Context, Result};
+use once_cell::sync::OnceCell;
+use reqwest::Proxy;
+use std::{
+    sync::atomic::{AtomicU32, Ordering},
+    time::Duration,
+};
+
+static NETWORK_OPTIONS: OnceCell<NetworkOptions> = OnceCell::new();
+static CLIENT: OnceCell<reqwest::Client> = OnceCell::new();
+static BLOCKING_CLIENT: OnceCell<reqwest::blocking::Client> = OnceCell::new();
+/// Reconnection attempts since the last successful connection, of all streams together
+static RETRIES: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Debug, Clone)]
+pub struct NetworkOptions {
+    /// Send all requests through this proxy.
+    /// If `None`, `HTTP_PROXY` and `HTTPS_PROXY` environment variables are used.
+    pub proxy: Option<String>,
+    /// Timeout for connecting, and for requests that are not streams.
+    /// For blocking requests, this is also the timeout of each read from the response body.
+    pub timeout: Duration,
+    /// Max reconnection attempts of the now playing info stream and the audio stream together,
+    /// since the last successful connection. 0 to retry forever.
+    pub max_retries: u32,
+}
+
+impl Default for NetworkOptions {
+    fn default() -> Self {
+        Self {
+            proxy: None,
+            timeout: Duration::from_secs(10),
+            max_retries: 0,
+        }
+    }
+}
+
+/// Set network options for all clients. Call this before any request is made.
+pub fn init(options: NetworkOptions) -> Result<()> {
+    // Build the async client now to validate the options
+    let client = build_client(&options)?;
+    let _ = CLIENT.set(client);
+    let _ = NETWORK_OPTIONS.set(options);
+    Ok(())
+}
+
+fn network_options() -> &'static NetworkOptions {
+    NETWORK_OPTIONS.get_or_init(NetworkOptions::default)
+}
+
+/// Timeout for a whole request, for requests that are not streams.
+/// Apply it with `RequestBuilder::timeout`.
+pub fn request_timeout() -> Duration {
+    network_options().timeout
+}
+
+/// reqwest's timeout error doesn't say which server timed out after how long, so add that.
+pub fn explain_timeout(error: reqwest::Error) -> anyhow::Error {
+    if !error.is_timeout() {
+        return error.into();
+    }
+
+    let server = error
+        .url()
+        .and_then(|url| url.host_str())
+        .unwrap_or("Server")
+        .to_owned();
+    let timeout_seconds = network_options().timeout.as_secs_f64();
+    anyhow!(error).context(format!(
+        "{} did not respond within {} seconds. Is it reachable?",
+        server, timeout_seconds
+    ))
+}
+
+/// Count a reconnection attempt. Returns `false` if there are no attempts left, so the caller should give up.
+pub fn try_retry() -> bool {
+    let max_retries = network_options().max_retries;
+    let retries = RETRIES.fetch_add(1, Ordering::Relaxed) + 1;
+    max_retries == 0 || retries <= max_retries
+}
+
+/// A stream connected, so the attempts start over.
+pub fn reset_retries() {
+    RETRIES.store(0, Ordering::Relaxed);
+}
+
+/// Whether a stream gave up reconnecting because of `NetworkOptions::max_retries`.
+pub fn is_out_of_retries() -> bool {
+    let max_retries = network_options().max_retries;
+    max_retries > 0 && RETRIES.load(Ordering::Relaxed) > max_retries
+}
+
+pub fn out_of_retries_error() -> anyhow::Error {
+    anyhow!(
+        "Giving up after {} attempts — check your connection",
+        network_options().max_retries
+    )
+}
+
+/// The client for async requests.
+pub fn client() -> &'static reqwest::Client {
+    CLIENT.get_or_init(|| build_client(network_options()).expect("Network options should be valid"))
+}
+
+/// The client for blocking requests, like on the player thread.
+///
+/// Must not be called from async code for the first time,
+/// since creating a blocking client inside an async runtime panics.
+pub fn blocking_client() -> &'static reqwest::blocking::Client {
+    BLOCKING_CLIENT.get_or_init(|| {
+        build_blocking_client(network_options()).expect("Network options should be valid")
+    })
+}
+
+fn build_client(options: &NetworkOptions) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder().connect_timeout(options.timeout);
+    if let Some(proxy) = &options.proxy {
+        builder = builder.proxy(parse_proxy(proxy)?);
+    }
+    Ok(builder.build()?)
+}
+
+fn build_blocking_client(options: &NetworkOptions) -> Result<reqwest::blocking::Client> {
+    let mut builder = reqwest::blocking::Client::builder()
+        .connect_timeout(options.timeout)
+        .timeout(options.timeout);
+    if let Some(proxy) = &options.proxy {
+        builder = builder.proxy(parse_proxy(proxy)?);
+    }
+    Ok(builder.build()?)
+}
+
+fn parse_proxy(proxy: &str) -> Result<Proxy> {
+    Proxy::all(proxy).with_context(|| format!("Invalid proxy URL \"{}\"", proxy))
+}
@@ -0,0 +1,97 @@
+//! ICY (Icecast/SHOUTcast) inline metadata, a fallback for song info when the API is unavailable.
+//!
+//! If a stream is requested with `Icy-MetaData: 1` header and the server supports it,
+//! the response has an `icy-metaint` header, and a metadata block is inserted after every `icy-metaint` audio bytes.
+//! Each block starts with a length byte (in 16 bytes), followed by text like `StreamTitle='Artist - Title';`.
+
+use std::{
+    io::{self, Read},
+    sync::Arc,
+};
+use tokio::sync::watch;
+
+pub const REQUEST_HEADER: &str = "Icy-MetaData";
+pub const METADATA_INTERVAL_HEADER: &str = "icy-metaint";
+
+/// A reader wrapper which strips metadata blocks from a stream, so only audio bytes are left,
+/// and sends each new `StreamTitle` to `title_sender`.
+pub struct IcyMetadataReader<R>
+where
+    R: Read,
+{
+    inner: R,
+    metadata_interval: usize,
+    bytes_until_metadata: usize,
+    title_sender: Arc<watch::Sender<Option<String>>>,
+}
+
+impl<R> IcyMetadataReader<R>
+where
+    R: Read,
+{
+    /// `metadata_interval` is the value of the response's `icy-metaint` header.
+    pub fn new(
+        inner: R,
+        metadata_interval: usize,
+        title_sender: Arc<watch::Sender<Option<String>>>,
+    ) -> Self {
+        Self {
+            inner,
+            metadata_interval,
+            bytes_until_metadata: metadata_interval,
+            title_sender,
+        }
+    }
+
+    fn read_metadata(&mut self) -> io::Result<()> {
+        let mut length = [0; 1];
+        self.inner.read_exact(&mut length)?;
+        if length[0] == 0 {
+            return Ok(()); // Metadata didn't change
+        }
+
+        let mut metadata = vec![0; usize::from(length[0]) * 16];
+        self.inner.read_exact(&mut metadata)?;
+
+        if let Some(title) = parse_stream_title(&metadata) {
+            if self.title_sender.borrow().as_deref() != Some(title.as_str()) {
+                log::info!("ICY stream title: {}", title);
+                self.title_sender.send_replace(Some(title));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R> Read for IcyMetadataReader<R>
+where
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.bytes_until_metadata == 0 {
+            self.read_metadata()?;
+            self.bytes_until_metadata = self.metadata_interval;
+        }
+
+        let max_len = buf.len().min(self.bytes_until_metadata);
+        let len = self.inner.read(&mut buf[..max_len])?;
+        self.bytes_until_metadata -= len;
+        Ok(len)
+    }
+}
+
+/// Get `Artist - Title` out of a metadata block like `StreamTitle='Artist - Title';StreamUrl='';`.
+/// Titles may contain `'`, so the value ends at `';`, or at the last `'` if there's no `';`.
+fn parse_stream_title(metadata: &[u8]) -> Option<String> {
+    const KEY: &str = "StreamTitle='";
+
+    let metadata = String::from_utf8_lossy(metadata);
+    let metadata = metadata.trim_end_matches('\0');
+
+    let value_start = metadata.find(KEY)? + KEY.len();
+    let value = &metadata[value_start..];
+    let value_end = value.find("';").or_else(|| value.rfind('\''))?;
+
+    let title = value[..value_end].trim();
+    (!title.is_empty()).then(|| title.to_owned())
+}
@@ -0,0 +1,54 @@
+//! Code Radio integration behind the `code-radio` command line client, for building other clients.
+//!
+//! The public API is the API client and the player:
+//!
+//! - [`code_radio_api`]: Get now playing info and stations, with REST or Server-Sent Events API
+//! - [`models`]: Types of now playing info returned by the API
+//! - [`http_client`]: Network options like proxy and timeout, shared by all requests
+//! - [`player`]: Stream a station's audio to an [`audio_backend`]
+//!
+//! Modules hidden from these docs are shared with the binary and its tests only, and may change in any release.
+//!
+//! ```no_run
+//! # async fn run() -> anyhow::Result<()> {
+//! use code_radio_cli::{code_radio_api, player::{Player, PlayerOptions}};
+//!
+//! let message = code_radio_api::get_message().await?;
+//! println!("Now playing: {}", message.now_playing.song.title);
+//!
+//! let mut player = Player::try_new(PlayerOptions {
+//!     audio_backend: code_radio_cli::audio_backend::AudioBackendKind::Rodio,
+//!     device: None,
+//!     reconnect_attempts: 5,
+//!     record_path: None,
+//!     record_split_dir: None,
+//!     fade: std::time::Duration::from_millis(500),
+//!     gain: 1.0,
+//!     buffer_size: 64 * 1024,
+//!     mono: false,
+//! })?;
+//! player.play(&message.station.listen_url, None);
+//! # Ok(())
+//! # }
+//! ```
+
+#[doc(hidden)]
+pub mod app_state;
+pub mod audio_backend;
+pub mod code_radio_api;
+pub mod http_client;
+pub mod models;
+pub mod player;
+#[doc(hidden)]
+pub mod utils;
+
+mod icy_metadata;
+mod mono_downmix;
+mod mp3_stream_decoder;
+mod read_ahead_reader;
+mod sample_activity;
+mod sse_client;
+mod stereo_balance;
+mod stream_decoder;
+mod symphonia_stream_decoder;
+mod tee_reader;
@@ -0,0 +1,146 @@
+use anyhow::{Context, Result};
+use log::{LevelFilter, Log, Metadata, Record};
+use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// When the log file grows beyond this size, it will be renamed to "<log file>.old" and a new one will be started,
+/// so a multi-day session won't fill up the disk.
+const MAX_LOG_FILE_SIZE: u64 = 5 * 1024 * 1024;
+
+/// Route diagnostic output from `log` macros to `log_file`.
+pub fn init_file_logger(log_file: &Path, level: LevelFilter) -> Result<()> {
+    let logger = FileLogger::try_new(log_file)
+        .with_context(|| format!("Failed to open log file \"{}\"", log_file.display()))?;
+
+    log::set_boxed_logger(Box::new(logger))?;
+    log::set_max_level(level);
+
+    Ok(())
+}
+
+/// Route diagnostic output from `log` macros to `print_line`, like printing to stderr.
+pub fn init_print_logger(print_line: fn(&str), level: LevelFilter) -> Result<()> {
+    log::set_boxed_logger(Box::new(PrintLogger { print_line }))?;
+    log::set_max_level(level);
+
+    Ok(())
+}
+
+/// Debug logs of dependencies, like the HTTP client, are too noisy to be useful,
+/// so only this crate's debug logs are enabled.
+fn is_enabled(metadata: &Metadata) -> bool {
+    metadata.level() <= log::max_level()
+        && (metadata.level() <= log::Level::Info || metadata.target().starts_with("code_radio"))
+}
+
+fn format_line(record: &Record) -> String {
+    format!(
+        "[{}] {:<5} {}: {}",
+        get_timestamp(),
+        record.level(),
+        record.target(),
+        record.args()
+    )
+}
+
+struct PrintLogger {
+    print_line: fn(&str),
+}
+
+impl Log for PrintLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        is_enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            (self.print_line)(&format_line(record));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+struct FileLogger {
+    path: PathBuf,
+    file: Mutex<LogFile>,
+}
+
+struct LogFile {
+    file: File,
+    size: u64,
+}
+
+impl FileLogger {
+    fn try_new(path: &Path) -> std::io::Result<Self> {
+        let file = LogFile::open(path)?;
+        Ok(Self {
+            path: path.to_owned(),
+            file: Mutex::new(file),
+        })
+    }
+
+    fn rotate(&self, log_file: &mut LogFile) -> std::io::Result<()> {
+        let mut old_path = self.path.clone().into_os_string();
+        old_path.push(".old");
+        std::fs::rename(&self.path, old_path)?;
+        *log_file = LogFile::open(&self.path)?;
+        Ok(())
+    }
+}
+
+impl LogFile {
+    fn open(path: &Path) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(Self { file, size })
+    }
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        is_enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let line = format_line(record) + "\n";
+
+        let mut log_file = self.file.lock().unwrap();
+        if log_file.size + line.len() as u64 > MAX_LOG_FILE_SIZE {
+            if let Err(e) = self.rotate(&mut log_file) {
+                // Like when another program holds the file open on Windows.
+                // Start over in place, instead of retrying on every line while the file keeps growing.
+                let _ = log_file.file.set_len(0);
+                log_file.size = 0;
+                let notice = format!("Failed to rotate log file, truncated it instead: {}\n", e);
+                if log_file.file.write_all(notice.as_bytes()).is_ok() {
+                    log_file.size += notice.len() as u64;
+                }
+            }
+        }
+        if log_file.file.write_all(line.as_bytes()).is_ok() {
+            log_file.size += line.len() as u64;
+        }
+    }
+
+    fn flush(&self) {
+        let _ = self.file.lock().unwrap().file.flush();
+    }
+}
+
+/// Unix timestamp with milliseconds, like "1670000000.123"
+fn get_timestamp() -> String {
+    let duration = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    format!("{}.{:03}", duration.as_secs(), duration.subsec_millis())
+}
@@ -1,295 +1,2513 @@
+mod album_art;
+mod app_dirs;
 mod args;
-mod code_radio_api;
-mod models;
-mod mp3_stream_decoder;
-mod player;
+mod clipboard;
+mod clock;
+mod config;
+mod doctor;
+mod exit_code;
+mod favorites;
+mod hangup;
+mod logger;
+mod media_controls;
+mod metadata_debounce;
+mod metadata_log;
+mod notification;
+mod persisted_state;
+mod song_change_command;
+mod song_progress;
 mod terminal;
+mod top_artists;
+mod tui;
 mod update_checker;
-mod utils;
+mod webhook;
 
 use anyhow::{anyhow, Context, Result};
-use args::Args;
-use clap::Parser;
+use app_state::{AppState, UiEvent};
+use args::{Args, DefaultAction, ListenerMetric, PauseMode, Volume, VolumePreset};
+use audio_backend::AudioBackendKind;
+use clock::SystemClock;
+use code_radio_cli::{
+    app_state, audio_backend, code_radio_api, http_client, models, player, utils,
+};
 use colored::Colorize;
+use exit_code::{ExitCode, WithExitCode};
 use futures_util::StreamExt;
-use indicatif::{ProgressBar, ProgressState, ProgressStyle};
-use inquire::Select;
-use models::code_radio::{CodeRadioMessage, Remote};
-use player::Player;
-use rodio::Source;
-use std::{fmt::Write, sync::Mutex, thread, time::Duration};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressState, ProgressStyle};
+use inquire::{InquireError, Select};
+use media_controls::MediaCommand;
+use models::code_radio::{CodeRadioMessage, Listeners, Live, NowPlaying, Remote, Song};
+use once_cell::sync::{Lazy, OnceCell};
+use player::{Player, PlayerOptions, PlayerState, StreamFormat};
+use song_progress::SongProgress;
+use std::{
+    collections::VecDeque,
+    fmt::Write,
+    io::IsTerminal,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use tokio::sync::{watch, Notify};
 
 const LOADING_SPINNER_TICK_INTERVAL: Duration = Duration::from_millis(120);
+const STREAM_START_TIMEOUT: Duration = Duration::from_secs(15);
+/// Refreshing more often than this only costs CPU, since the progress is shown in seconds.
+const MIN_REFRESH_INTERVAL_MILLIS: u64 = 100;
+/// Format of `--now-playing-file` and `--oneshot` when neither `--now-playing-format` nor `--format` is set.
+const DEFAULT_NOW_PLAYING_FORMAT: &str = "{artist} - {title}";
+/// How many times to try getting the first now playing info before giving up.
+const INITIAL_MESSAGE_ATTEMPTS: u32 = 3;
+/// How long to wait for the API to report a song change, before showing the stream's ICY title instead.
+const ICY_TITLE_FALLBACK_DELAY: Duration = Duration::from_secs(30);
+/// How often the `--tui` interface redraws when no key is pressed.
+const TUI_REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+/// How many recent listener counts to keep for the sparkline.
+const LISTENER_HISTORY_LEN: usize = 30;
+/// Room to leave for the rest of the progress bar line when fitting the listener sparkline into the terminal.
+const LISTENER_SPARKLINE_RESERVED_COLUMNS: usize = 64;
+/// Color the progress bar differently this many seconds before a song ends, as a cue of the song change
+const SONG_ENDING_SECONDS: u64 = 15;
+/// Download the next song's album art this many seconds before the current song ends, with `--prefetch-art`.
+const PREFETCH_ART_SECONDS: u64 = 30;
 
 static PLAYER: Mutex<Option<Player>> = Mutex::new(None);
 static PROGRESS_BAR: Mutex<Option<ProgressBar>> = Mutex::new(None);
+static SONG_PROGRESS: Mutex<Option<SongProgress>> = Mutex::new(None);
+static LATEST_MESSAGE: Mutex<Option<CodeRadioMessage>> = Mutex::new(None);
+/// Notified when the user asks to quit, or the player gives up reconnecting.
+static SHUTDOWN: Lazy<Notify> = Lazy::new(Notify::new);
+/// Why the player gave up reconnecting, if it did. Checked on `SHUTDOWN` to exit with an error.
+static PLAYER_GAVE_UP: Mutex<Option<String>> = Mutex::new(None);
+/// Whether the station prompt is open during playback. The progress bar is hidden meanwhile.
+static STATION_PROMPT_OPEN: AtomicBool = AtomicBool::new(false);
+/// Whether to display volume as a percentage, set by `--volume-percent`.
+static SHOW_VOLUME_PERCENT: AtomicBool = AtomicBool::new(false);
+/// Whether resuming after pausing jumps to the live stream, set by `--pause-mode live`.
+static RESUME_LIVE: AtomicBool = AtomicBool::new(false);
+/// `--volume-preset`s, to switch between by pressing P.
+static VOLUME_PRESETS: OnceCell<Vec<VolumePreset>> = OnceCell::new();
+/// Index of the volume preset to switch to on the next P press.
+static NEXT_VOLUME_PRESET: AtomicUsize = AtomicUsize::new(0);
+/// Whether the stream is stalled, so the progress bar shows "Buffering..." instead of listeners.
+static STREAM_BUFFERING: AtomicBool = AtomicBool::new(false);
+/// When the sleep timer set by `--sleep` ends.
+static SLEEP_TIMER_END: OnceCell<Instant> = OnceCell::new();
+/// The next song's album art URL, until `--prefetch-art` downloads it near the end of the current song.
+static NEXT_ART_URL: Mutex<Option<String>> = Mutex::new(None);
+/// Listener counts of the most recent messages, oldest first, at most `LISTENER_HISTORY_LEN` of them.
+static LISTENER_HISTORY: Mutex<VecDeque<i64>> = Mutex::new(VecDeque::new());
+
+fn main() {
+    media_controls::run_app(run);
+}
 
 #[tokio::main]
-async fn main() {
-    terminal::enable_color_on_windows();
-    let _terminal_clean_up_helper = terminal::create_clean_up_helper(); // See the comments in "terminal" module
+async fn run() {
+    terminal::init_colors();
+    let args = match config::parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            terminal::print_error(format!("{:#}", e));
+            exit_code::exit(ExitCode::InvalidArguments);
+        }
+    };
+    if args.json || args.daemon || args.no_color {
+        terminal::disable_colors();
+    }
 
-    if let Err(e) = start().await {
+    // See the comments in "terminal" module. Daemon mode never reads keys, so there is nothing to clean up.
+    let _terminal_clean_up_helper =
+        (!args.no_terminal_cleanup && !args.daemon && terminal::can_read_keys())
+            .then(terminal::create_clean_up_helper);
+
+    if let Err(e) = start(args).await {
+        tui::restore_terminal();
+        log::error!("{:#}", e);
         println!();
-        terminal::print_error(e);
+        terminal::print_error(&e);
+        exit_code::exit(exit_code::get_exit_code(&e));
+    }
+}
+
+async fn start(args: Args) -> Result<()> {
+    let log_level = if args.verbose {
+        log::LevelFilter::Debug
+    } else {
+        log::LevelFilter::Info
+    };
+    if let Some(log_file) = &args.log_file {
+        logger::init_file_logger(log_file, log_level)?;
+    } else if args.verbose {
+        logger::init_print_logger(print_log_line, log_level)?;
+    }
+    log::info!("Code Radio CLI v{} started", env!("CARGO_PKG_VERSION"));
+
+    validate_args(&args).exit_code(ExitCode::InvalidArguments)?;
+
+    SHOW_VOLUME_PERCENT.store(args.volume_percent, Ordering::Relaxed);
+    RESUME_LIVE.store(args.pause_mode == PauseMode::Live, Ordering::Relaxed);
+    let volume_presets = args.get_volume_presets();
+    // Pressing P continues from `--preset`
+    if let Some(index) = args
+        .preset
+        .as_ref()
+        .and_then(|name| volume_presets.iter().position(|p| &p.name == name))
+    {
+        NEXT_VOLUME_PRESET.store(index + 1, Ordering::Relaxed);
+    }
+    let _ = VOLUME_PRESETS.set(volume_presets);
+
+    if let Some(cache_dir) = &args.cache_dir {
+        app_dirs::set_cache_dir(cache_dir.clone());
+    }
+
+    if args.list_devices {
+        return print_output_devices().exit_code(ExitCode::AudioDevice);
+    }
+
+    http_client::init(http_client::NetworkOptions {
+        proxy: args.proxy.clone(),
+        timeout: Duration::from_secs(args.timeout),
+        max_retries: args.max_retries,
+    })
+    .exit_code(ExitCode::InvalidArguments)?;
+    code_radio_api::set_api_url(&args.api_url, &args.station_shortcode)
+        .exit_code(ExitCode::InvalidArguments)?;
+    code_radio_api::set_reconnect_delays(
+        Duration::from_secs(args.reconnect_initial),
+        Duration::from_secs(args.reconnect_max),
+    );
+
+    if let Some(simulate_file) = &args.simulate {
+        return simulate(simulate_file, &args).await;
+    }
+
+    if args.oneshot {
+        return print_now_playing_once(&args).await;
+    }
+
+    if args.doctor {
+        return doctor::run(get_player_options(&args)).await;
+    }
+
+    if let Some(url) = &args.url {
+        return play_url(url, &args).await;
+    }
+
+    if args.list_stations {
+        return print_stations().await;
+    }
+
+    if let Some(station_query) = &args.favorite {
+        return add_favorite_station(station_query).await;
+    }
+
+    if args.favorites {
+        return print_favorite_stations().await;
+    }
+
+    if let Some(n) = args.top_artists {
+        return print_top_artists(n).await;
+    }
+
+    start_playing(args).await?;
+
+    Ok(())
+}
+
+/// Check arguments which clap can't check by itself, before doing anything.
+fn validate_args(args: &Args) -> Result<()> {
+    if !(args.simulate_speed.is_finite() && args.simulate_speed > 0.0) {
+        return Err(anyhow!("Simulate speed must be a positive number"));
+    }
+
+    if !(args.gain.is_finite() && args.gain > 0.0) {
+        return Err(anyhow!("Gain must be a positive number"));
+    }
+
+    if args.sleep == Some(0) {
+        return Err(anyhow!("Sleep timer must be at least 1 minute"));
+    }
+
+    if args.timeout == 0 {
+        return Err(anyhow!("Timeout must be at least 1 second"));
+    }
+
+    if args.reconnect_initial == 0 {
+        return Err(anyhow!("Reconnect initial delay must be at least 1 second"));
+    }
+    if args.reconnect_max < args.reconnect_initial {
+        return Err(anyhow!(
+            "Reconnect max delay must be at least the initial delay"
+        ));
+    }
+
+    if (1..MIN_REFRESH_INTERVAL_MILLIS).contains(&args.refresh) {
+        return Err(anyhow!(
+            "Refresh interval must be 0 or at least {} milliseconds",
+            MIN_REFRESH_INTERVAL_MILLIS
+        ));
+    }
+
+    if args.tui && args.audio_backend == AudioBackendKind::PcmStdout {
+        return Err(anyhow!(
+            "The full-screen interface can't be used with the pcm-stdout audio backend"
+        ));
+    }
+
+    if let Some(webhook_url) = &args.webhook {
+        reqwest::Url::parse(webhook_url).context("Invalid webhook URL")?;
+    }
+
+    if let Some(url) = &args.url {
+        reqwest::Url::parse(url).context("Invalid stream URL")?;
+    }
+
+    if let Some(preset) = &args.preset {
+        if !args.volume_preset.iter().any(|p| &p.name == preset) {
+            return Err(anyhow!("Volume preset \"{}\" not found", preset));
+        }
+    }
+
+    Ok(())
+}
+
+/// Replay messages recorded with `--dump-metadata` through the display, without any network or audio.
+async fn simulate(simulate_file: &Path, args: &Args) -> Result<()> {
+    let messages = metadata_log::read_messages(simulate_file)?;
+
+    let mut app_state = AppState::new();
+    let mut last_server_timestamp: Option<i64> = None;
+    start_ticking_progress_bar(args);
+
+    for message in messages {
+        let server_timestamp = metadata_log::get_server_timestamp(&message);
+        if let Some(last_server_timestamp) = last_server_timestamp {
+            let delay_seconds =
+                (server_timestamp - last_server_timestamp).max(0) as f64 / args.simulate_speed;
+            tokio::time::sleep(Duration::from_secs_f64(delay_seconds)).await;
+        }
+        last_server_timestamp = Some(server_timestamp);
+
+        update_song_info_on_screen(message, &mut app_state, args).await;
+    }
+
+    update_progress_bar(ProgressBar::finish);
+    Ok(())
+}
+
+/// Print the current song once in the format of `--json` or `--format`, without playing anything.
+async fn print_now_playing_once(args: &Args) -> Result<()> {
+    let message = code_radio_api::get_message().await?;
+
+    if args.json {
+        print_message_as_json(&message, args.listener_metric);
+    } else {
+        let format = args.format.as_deref().unwrap_or(DEFAULT_NOW_PLAYING_FORMAT);
+        println!(
+            "{}",
+            format_now_playing(format, &message, args.listener_metric)
+        );
+    }
+
+    Ok(())
+}
+
+async fn print_stations() -> Result<()> {
+    let stations = code_radio_api::get_stations().await?;
+    print_station_table(&stations.iter().collect::<Vec<_>>());
+    Ok(())
+}
+
+async fn add_favorite_station(station_query: &str) -> Result<()> {
+    let stations = code_radio_api::get_stations().await?;
+    let station = code_radio_api::find_station(&stations, station_query)
+        .exit_code(ExitCode::InvalidArguments)?;
+
+    if favorites::add(station.id).await? {
+        println!("Added {} to favorites", station.name.bright_green());
+    } else {
+        println!("{} is already a favorite", station.name.bright_green());
+    }
+    Ok(())
+}
+
+async fn print_favorite_stations() -> Result<()> {
+    let favorite_ids = favorites::load_ids().await;
+    if favorite_ids.is_empty() {
+        println!("No favorite stations. Add one with --favorite <NAME_OR_SHORTCODE>");
+        return Ok(());
+    }
+
+    let stations = code_radio_api::get_stations().await?;
+    print_station_table(&favorites::resolve(&favorite_ids, &stations));
+    Ok(())
+}
+
+fn print_station_table(stations: &[&Remote]) {
+    let name_width = stations
+        .iter()
+        .map(|s| s.name.chars().count())
+        .chain(std::iter::once("Name".len()))
+        .max()
+        .unwrap_or_default();
+
+    println!(
+        "{}",
+        format!(
+            "{:>4}  {:<name_width$}  {:>7}  {}",
+            "ID", "Name", "Bitrate", "Format"
+        )
+        .bright_green()
+    );
+    for station in stations {
+        println!(
+            "{:>4}  {:<name_width$}  {:>7}  {}",
+            station.id,
+            station.name,
+            format!("{}k", station.bitrate),
+            station.format
+        );
+    }
+}
+
+fn print_output_devices() -> Result<()> {
+    let devices = audio_backend::list_output_devices()?;
+    if devices.is_empty() {
+        println!("No audio output devices found");
+        return Ok(());
+    }
+
+    for device in &devices {
+        if device.is_default {
+            println!("{} {}", device.name, "(default)".bright_green());
+        } else {
+            println!("{}", device.name);
+        }
+    }
+
+    Ok(())
+}
+
+async fn print_top_artists(n: usize) -> Result<()> {
+    let message = code_radio_api::get_message().await?;
+    let top_artists = top_artists::get_top_artists(&message.song_history, n);
+
+    println!(
+        "{}",
+        format!(
+            "Top artists in the last {} songs:",
+            message.song_history.len()
+        )
+        .bright_green()
+    );
+    for (i, (artist, count)) in top_artists.iter().enumerate() {
+        println!("{:>3}. {} ({})", i + 1, artist, count);
+    }
+
+    Ok(())
+}
+
+async fn start_playing(args: Args) -> Result<()> {
+    // Check update in background
+    let update_checking_task = args
+        .is_update_check_enabled()
+        .then(|| tokio::spawn(update_checker::get_new_release()));
+
+    if args.is_display_enabled() {
+        display_welcome_message(&args);
+    }
+
+    // Nobody is there to answer the prompt in daemon mode
+    let select_station = args.select_station
+        || (args.station.is_none() && args.default_action == DefaultAction::Select && !args.daemon);
+
+    let selected_station: Option<Remote> = if select_station {
+        match select_station_interactively().await? {
+            Some(station) => {
+                let station_id = station.id;
+                if let Err(e) =
+                    persisted_state::update(|state| state.last_station_id = Some(station_id)).await
+                {
+                    log::warn!("Failed to save selected station: {:#}", e);
+                }
+                Some(station)
+            }
+            None => return Ok(()), // User cancelled the prompt
+        }
+    } else {
+        None
+    };
+
+    let resume_station =
+        args.resume || (args.station.is_none() && args.default_action == DefaultAction::Resume);
+    let last_station_id = if resume_station {
+        persisted_state::load().await.last_station_id
+    } else {
+        None
+    };
+
+    let play_favorite = args.station.is_none() && args.default_action == DefaultAction::Favorite;
+    let favorite_ids = if play_favorite {
+        favorites::load_ids().await
+    } else {
+        Vec::new()
+    };
+
+    // Fetching data in background while creating `Player` to improve startup speed
+    // Note: Here we use the REST API to get the first API message,
+    // because getting the first message from the Server-Sent Events stream may be slow
+    let get_message_task = tokio::spawn(code_radio_api::get_message());
+    let mut message_stream = code_radio_api::get_message_stream_with_fallback();
+
+    let loading_spinner = ProgressBar::new_spinner()
+        .with_style(ProgressStyle::with_template("{spinner} {msg}")?)
+        .with_message("Initializing audio device...");
+    if args.daemon || !terminal::is_stdout_terminal() {
+        loading_spinner.set_draw_target(ProgressDrawTarget::hidden());
+    } else {
+        loading_spinner.enable_steady_tick(LOADING_SPINNER_TICK_INTERVAL);
+    }
+
+    let volume = get_initial_volume(&args).await;
+    prepare_player_options(&args, &loading_spinner)?;
+
+    // Creating a `Player` might be time consuming. It might take several seconds on first run.
+    match Player::try_new(get_player_options(&args)) {
+        Ok(mut player) => {
+            player.set_volume(volume);
+            player.set_balance(args.balance);
+            PLAYER.lock().unwrap().replace(player);
+            media_controls::start(handle_media_command);
+        }
+        // A device asked for by name is worth failing for, unlike the default one
+        Err(e) if args.device.is_some() => return Err(e).exit_code(ExitCode::AudioDevice),
+        Err(e) => {
+            // Still useful as a track ticker, like over SSH or in a container
+            log::error!("{:#}", e);
+            if args.is_display_enabled() {
+                let notice = format!("No audio device, showing now playing info only: {:#}", e);
+                loading_spinner.suspend(|| {
+                    println!("{}", notice.bright_yellow());
+                    println!();
+                });
+            }
+        }
+    }
+
+    loading_spinner.set_message("Connecting...");
+    let message = match get_message_task.await? {
+        Ok(message) => message,
+        Err(e) => retry_get_message(e, &loading_spinner).await?,
+    };
+
+    let stations = code_radio_api::get_stations_from_message(&message);
+
+    let listen_url = match (selected_station, &args.station) {
+        (Some(ref station), _) => stations
+            .iter()
+            .find(|s| s.id == station.id)
+            .context(anyhow!("Station with ID \"{}\" not found", station.id))?
+            .url
+            .clone(),
+        (None, Some(station_query)) => code_radio_api::find_station(&stations, station_query)
+            .exit_code(ExitCode::InvalidArguments)?
+            .url
+            .clone(),
+        (None, None) if resume_station => {
+            match last_station_id.and_then(|id| stations.iter().find(|s| s.id == id)) {
+                Some(station) => station.url.clone(),
+                None => {
+                    let notice = "Last selected station not found, playing the default station";
+                    log::warn!("{}", notice);
+                    if args.is_display_enabled() {
+                        loading_spinner.suspend(|| println!("{}", notice.bright_yellow()));
+                    }
+                    message.station.listen_url.clone()
+                }
+            }
+        }
+        (None, None) if play_favorite => {
+            match favorites::resolve(&favorite_ids, &stations).first() {
+                Some(station) => station.url.clone(),
+                None => {
+                    let notice = "No favorite stations found, playing the default station";
+                    log::warn!("{}", notice);
+                    if args.is_display_enabled() {
+                        loading_spinner.suspend(|| println!("{}", notice.bright_yellow()));
+                    }
+                    message.station.listen_url.clone()
+                }
+            }
+        }
+        (None, None) => match args
+            .bitrate
+            .and_then(|bitrate| code_radio_api::find_station_by_bitrate(&stations, bitrate))
+        {
+            Some(station) => {
+                let notice = format!(
+                    "Playing the {}kbps stream, the closest to {}kbps",
+                    station.bitrate,
+                    args.bitrate.unwrap_or_default()
+                );
+                log::info!("{}", notice);
+                if args.is_display_enabled() {
+                    loading_spinner.suspend(|| println!("{}", notice));
+                }
+                station.url.clone()
+            }
+            None => message.station.listen_url.clone(),
+        },
+    };
+
+    log::info!("Listen URL: {}", listen_url);
+    let stream_format = stations
+        .iter()
+        .find(|station| station.url == listen_url)
+        .and_then(|station| StreamFormat::from_name(&station.format));
+    let player_state = PLAYER.lock().unwrap().as_mut().map(|player| {
+        player.play(&listen_url, stream_format);
+        player.state()
+    });
+
+    // Only show the station once audio is confirmed flowing, so users never see it with silence
+    if let Some(player_state) = player_state {
+        loading_spinner.set_message("Buffering...");
+        tokio::time::timeout(
+            STREAM_START_TIMEOUT,
+            player::wait_until_playing(player_state.clone()),
+        )
+        .await
+        .map_err(|_| anyhow!("Timed out waiting for audio from {}", listen_url))??;
+
+        tokio::spawn(watch_player_state(
+            player_state,
+            args.is_display_enabled(),
+            args.listener_metric,
+        ));
+    }
+    loading_spinner.finish_and_clear();
+
+    // Notify user if a new version is available
+    if let Some(update_checking_task) =
+        update_checking_task.filter(|task| task.is_finished() && args.is_display_enabled())
+    {
+        match update_checking_task.await {
+            Ok(Ok(Some(new_release))) => {
+                println!(
+                    "{}",
+                    format!("New version available: {}", new_release.version).bright_yellow()
+                );
+                println!("{}", new_release.url.bright_yellow());
+                println!();
+            }
+            Ok(Err(e)) => log::warn!("Update check failed: {:#}", e),
+            _ => {}
+        }
+    }
+
+    if let Some(station) = stations.iter().find(|station| station.url == listen_url) {
+        if args.is_display_enabled() {
+            println!("{}    {}", "Station:".bright_green(), station.name);
+            let description = utils::sanitize_metadata_text(&message.station.description);
+            if !description.is_empty() {
+                let line = format!("{}", description.dimmed());
+                println!("{}", terminal::fit_to_width(&line));
+            }
+        }
+    }
+
+    if args.show_connection && args.is_display_enabled() {
+        print_connection_info();
+    }
+
+    spawn_shutdown_signal_handlers(&args);
+
+    let _title_guard = args.set_title.then(terminal::save_title);
+
+    let mut metadata_recorder = match &args.dump_metadata {
+        Some(path) => Some(metadata_log::MetadataRecorder::create(path)?),
+        None => None,
+    };
+    let mut record_metadata = |message: &CodeRadioMessage| {
+        if let Some(recorder) = metadata_recorder.as_mut() {
+            if let Err(e) = recorder.record(message) {
+                log::warn!("Failed to record metadata: {:#}", e);
+            }
+        }
+    };
+    record_metadata(&message);
+
+    let station_name = stations
+        .iter()
+        .find(|station| station.url == listen_url)
+        .map_or_else(
+            || message.station.name.clone(),
+            |station| station.name.clone(),
+        );
+
+    let mut app_state = AppState::new();
+    update_song_info_on_screen(message, &mut app_state, &args).await;
+    if args.is_display_enabled() {
+        start_ticking_progress_bar(&args);
+        // The bar fits itself to the terminal width whenever it's drawn, so redraw it right away
+        tokio::spawn(terminal::watch_resize(|| {
+            update_progress_bar(ProgressBar::tick)
+        }));
+        spawn_keyboard_input_handler();
+    }
+    if args.tui {
+        let runtime = tokio::runtime::Handle::current();
+        let listener_metric = args.listener_metric;
+        thread::spawn(move || run_tui(&runtime, station_name, listener_metric));
+    }
+
+    if let Some(sleep_minutes) = args.sleep {
+        tokio::spawn(run_sleep_timer(Duration::from_secs(sleep_minutes * 60)));
+    }
+
+    if args.is_display_enabled() || args.tui {
+        tokio::spawn(watch_message_parse_failures());
+    }
+
+    let metadata_debounce = Duration::from_millis(args.metadata_debounce);
+    let mut icy_title = PLAYER.lock().unwrap().as_ref().map(Player::icy_title);
+    let update_song_info = async {
+        // The stream's ICY title which changed, and when to show it if the API doesn't report a song change
+        let mut pending_icy_title: Option<(String, tokio::time::Instant)> = None;
+
+        loop {
+            let message = tokio::select! {
+                message = message_stream.next() => match message {
+                    Some(message) => message,
+                    None => break,
+                },
+                Some(title) = wait_for_icy_title_change(&mut icy_title) => {
+                    let show_at = tokio::time::Instant::now() + ICY_TITLE_FALLBACK_DELAY;
+                    pending_icy_title = Some((title, show_at));
+                    continue;
+                }
+                _ = tokio::time::sleep_until(
+                    pending_icy_title
+                        .as_ref()
+                        .map_or_else(tokio::time::Instant::now, |(_, show_at)| *show_at)
+                ), if pending_icy_title.is_some() => {
+                    if let Some((title, _)) = pending_icy_title.take() {
+                        show_icy_title(title, &mut app_state, &args).await;
+                    }
+                    continue;
+                }
+            };
+
+            if app_state.current_song_id() != Some(&message.now_playing.song.id) {
+                pending_icy_title = None;
+            }
+            // Collect messages arriving within the debounce window, to avoid redundant redraws
+            let messages =
+                metadata_debounce::collect_burst(message, &mut message_stream, metadata_debounce)
+                    .await;
+            for message in &messages {
+                record_metadata(message);
+            }
+
+            for message in metadata_debounce::coalesce(messages) {
+                update_song_info_on_screen(message, &mut app_state, &args).await;
+            }
+        }
+
+        if http_client::is_out_of_retries() {
+            return Err(http_client::out_of_retries_error());
+        }
+        Err(anyhow!("Now playing info stream ended unexpectedly"))
+    };
+
+    tokio::select! {
+        result = update_song_info => result,
+        _ = SHUTDOWN.notified() => {
+            check_player_gave_up()?;
+            log::info!("Shutting down");
+            PLAYER.lock().unwrap().take(); // Dropping `Player` stops playing
+            tui::restore_terminal();
+            update_progress_bar(ProgressBar::abandon);
+            println!();
+            Ok(())
+        }
+    }
+}
+
+/// Play `url` as is, like a plain Icecast stream, without any now playing info from the API.
+/// Only volume and elapsed time are displayed.
+async fn play_url(url: &str, args: &Args) -> Result<()> {
+    if args.is_display_enabled() {
+        display_welcome_message(args);
+    }
+
+    let loading_spinner = ProgressBar::new_spinner()
+        .with_style(ProgressStyle::with_template("{spinner} {msg}")?)
+        .with_message("Initializing audio device...");
+    if args.daemon || !terminal::is_stdout_terminal() {
+        loading_spinner.set_draw_target(ProgressDrawTarget::hidden());
+    } else {
+        loading_spinner.enable_steady_tick(LOADING_SPINNER_TICK_INTERVAL);
+    }
+
+    let volume = get_initial_volume(args).await;
+    prepare_player_options(args, &loading_spinner)?;
+
+    // Without now playing info, there's nothing to show without a player
+    let mut player = Player::try_new(get_player_options(args)).exit_code(ExitCode::AudioDevice)?;
+    player.set_volume(volume);
+    player.set_balance(args.balance);
+    log::info!("Playing {}", url);
+    player.play(url, None);
+    let player_state = player.state();
+    PLAYER.lock().unwrap().replace(player);
+    media_controls::start(handle_media_command);
+
+    loading_spinner.set_message("Buffering...");
+    tokio::time::timeout(
+        STREAM_START_TIMEOUT,
+        player::wait_until_playing(player_state.clone()),
+    )
+    .await
+    .map_err(|_| anyhow!("Timed out waiting for audio from {}", url))??;
+    tokio::spawn(watch_player_state(
+        player_state,
+        args.is_display_enabled(),
+        args.listener_metric,
+    ));
+    loading_spinner.finish_and_clear();
+
+    spawn_shutdown_signal_handlers(args);
+    let _title_guard = args.set_title.then(|| {
+        let title_guard = terminal::save_title();
+        terminal::set_title(url);
+        title_guard
+    });
+
+    if args.is_display_enabled() {
+        println!("{}    {}", "Station:".bright_green(), url);
+        println!();
+
+        let progress_bar_style =
+            ProgressStyle::with_template("{prefix}  {spinner} {elapsed}{sleep_timer}")?
+                .with_key("elapsed", |state: &ProgressState, write: &mut dyn Write| {
+                    let elapsed_seconds = state.elapsed().as_secs();
+                    let elapsed = utils::humanize_seconds_to_minutes_and_seconds(elapsed_seconds);
+                    write!(write, "{elapsed}").unwrap();
+                })
+                .with_key("sleep_timer", write_sleep_timer);
+        let progress_bar = ProgressBar::new_spinner()
+            .with_style(progress_bar_style)
+            .with_prefix(get_progress_bar_prefix(PLAYER.lock().unwrap().as_ref()));
+        if !terminal::is_stdout_terminal() {
+            progress_bar.set_draw_target(ProgressDrawTarget::hidden());
+        }
+        progress_bar.tick();
+        if args.refresh > 0 {
+            progress_bar.enable_steady_tick(LOADING_SPINNER_TICK_INTERVAL);
+        }
+        PROGRESS_BAR.lock().unwrap().replace(progress_bar);
+
+        tokio::spawn(terminal::watch_resize(|| {
+            update_progress_bar(ProgressBar::tick)
+        }));
+        spawn_keyboard_input_handler();
+    }
+
+    if let Some(sleep_minutes) = args.sleep {
+        tokio::spawn(run_sleep_timer(Duration::from_secs(sleep_minutes * 60)));
+    }
+
+    SHUTDOWN.notified().await;
+    check_player_gave_up()?;
+    log::info!("Shutting down");
+    PLAYER.lock().unwrap().take(); // Dropping `Player` stops playing
+    update_progress_bar(ProgressBar::abandon);
+    println!();
+    Ok(())
+}
+
+/// The volume of `--preset`, `--volume`, or the last used volume unless `--no-remember-volume` is set.
+/// Also starts remembering volume changes for the next launch.
+async fn get_initial_volume(args: &Args) -> u8 {
+    let preset_volume = args.preset.as_ref().and_then(|name| {
+        args.get_volume_presets()
+            .into_iter()
+            .find(|p| &p.name == name)
+            .map(|p| p.volume)
+    });
+    let volume = match preset_volume.or(args.volume) {
+        Some(volume) => volume.get(),
+        // The state file may have been edited by hand
+        None if !args.no_remember_volume => persisted_state::load()
+            .await
+            .volume
+            .filter(|&volume| volume <= Volume::MAX)
+            .unwrap_or(Volume::MAX),
+        None => Volume::MAX,
+    };
+    if !args.no_remember_volume {
+        persisted_state::start_saving_volume(volume);
+    }
+    volume
+}
+
+/// Check the options of `get_player_options` before creating a `Player`,
+/// failing early if the recording file can't be written, and warning about clipping.
+fn prepare_player_options(args: &Args, loading_spinner: &ProgressBar) -> Result<()> {
+    if let Some(record_path) = &args.record {
+        if args.record_split {
+            std::fs::create_dir_all(record_path).with_context(|| {
+                format!(
+                    "Failed to create recording directory {}",
+                    record_path.display()
+                )
+            })?;
+        } else {
+            // Start a new recording, and fail early if the file can't be written
+            std::fs::File::create(record_path).with_context(|| {
+                format!("Failed to create recording file {}", record_path.display())
+            })?;
+        }
+    }
+
+    if args.gain > 1.0 {
+        let warning = if args.gain > player::MAX_GAIN {
+            format!(
+                "Gain is capped at {}, which may clip on loud songs",
+                player::MAX_GAIN
+            )
+        } else {
+            "Gain above 1 may clip on loud songs".to_owned()
+        };
+        log::warn!("{}", warning);
+        if args.is_display_enabled() {
+            loading_spinner.suspend(|| {
+                println!("{}", warning.bright_yellow());
+                println!();
+            });
+        }
+    }
+
+    Ok(())
+}
+
+fn get_player_options(args: &Args) -> PlayerOptions {
+    PlayerOptions {
+        audio_backend: args.audio_backend,
+        device: args.device.clone(),
+        reconnect_attempts: args.reconnect_attempts,
+        record_path: args.record.clone().filter(|_| !args.record_split),
+        record_split_dir: args.record.clone().filter(|_| args.record_split),
+        fade: Duration::from_millis(args.fade),
+        gain: args.gain,
+        buffer_size: args.buffer.saturating_mul(1024),
+        mono: args.mono,
+    }
+}
+
+fn spawn_shutdown_signal_handlers(args: &Args) {
+    // Don't keep streaming as an orphan after the launcher is gone
+    if args.exit_on_hangup || (!args.daemon && !std::io::stdin().is_terminal()) {
+        tokio::spawn(async {
+            hangup::wait_for_hangup().await;
+            log::info!("Terminal or parent process closed, exiting");
+            SHUTDOWN.notify_one();
+        });
+    }
+
+    // Shut down the same way as quitting when a service manager or `kill` asks to,
+    // but keep playing when the terminal which launched the daemon closes
+    let ignore_hangup = args.daemon && !args.exit_on_hangup;
+    tokio::spawn(async move {
+        hangup::wait_for_termination_signal(ignore_hangup).await;
+        log::info!("Received termination signal, exiting");
+        SHUTDOWN.notify_one();
+    });
+}
+
+/// Retry getting the first now playing info after it failed with `first_error`, with backoff,
+/// so a transient error like a DNS hiccup doesn't fail the whole startup.
+async fn retry_get_message(
+    first_error: anyhow::Error,
+    loading_spinner: &ProgressBar,
+) -> Result<CodeRadioMessage> {
+    let mut error = first_error;
+    for attempt in 2..=INITIAL_MESSAGE_ATTEMPTS {
+        let delay = Duration::from_secs(1 << (attempt - 2));
+        log::warn!(
+            "Failed to get now playing info, retrying in {}s: {:#}",
+            delay.as_secs(),
+            error
+        );
+        loading_spinner.set_message(format!(
+            "Connecting... (retry {}/{})",
+            attempt - 1,
+            INITIAL_MESSAGE_ATTEMPTS - 1
+        ));
+        tokio::time::sleep(delay).await;
+
+        match code_radio_api::get_message().await {
+            Ok(message) => return Ok(message),
+            Err(e) => error = e,
+        }
+    }
+    Err(error)
+}
+
+/// Wait until the stream's ICY title changes. Never returns if there's no player.
+async fn wait_for_icy_title_change(
+    icy_title: &mut Option<watch::Receiver<Option<String>>>,
+) -> Option<String> {
+    match icy_title {
+        Some(receiver) => {
+            if receiver.changed().await.is_err() {
+                std::future::pending::<()>().await; // `Player` dropped
+            }
+            receiver.borrow().clone()
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// Show the stream's ICY title as the current song, when the API hasn't reported the song change,
+/// like during API outages. Only title and artist are known, and the song's duration is not.
+async fn show_icy_title(icy_title: String, app_state: &mut AppState, args: &Args) {
+    let mut message = match app_state.latest_message() {
+        Some(message) => message.clone(),
+        None => return,
+    };
+
+    let current_song = &message.now_playing.song;
+    if icy_title == format!("{} - {}", current_song.artist, current_song.title) {
+        return;
+    }
+    log::warn!(
+        "No song change from the API, showing ICY title instead: {}",
+        icy_title
+    );
+
+    let (artist, title) = icy_title.split_once(" - ").unwrap_or(("", &icy_title));
+    message.now_playing = NowPlaying {
+        song: Song {
+            id: format!("icy:{icy_title}"),
+            text: icy_title.clone(),
+            artist: artist.to_owned(),
+            title: title.to_owned(),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    message.playing_next = Default::default();
+
+    update_song_info_on_screen(message, app_state, args).await;
+}
+
+/// Quit after `duration`, fading out the volume during the last 30 seconds.
+async fn run_sleep_timer(duration: Duration) {
+    const FADE_OUT_DURATION: Duration = Duration::from_secs(30);
+
+    let _ = SLEEP_TIMER_END.set(Instant::now() + duration);
+
+    tokio::time::sleep(duration.saturating_sub(FADE_OUT_DURATION)).await;
+
+    let fade_out_duration = duration.min(FADE_OUT_DURATION);
+    let volume = PLAYER.lock().unwrap().as_ref().map_or(0, Player::volume);
+    if volume == 0 {
+        tokio::time::sleep(fade_out_duration).await;
+    }
+    let fade_out_step_duration = fade_out_duration / u32::from(volume.max(1));
+    for volume in (0..volume).rev() {
+        tokio::time::sleep(fade_out_step_duration).await;
+        // Don't go through `set_volume`, so the faded out volume is not remembered for the next launch
+        if let Some(player) = PLAYER.lock().unwrap().as_mut() {
+            player.set_volume(volume);
+            update_progress_bar(|p| p.set_prefix(get_progress_bar_prefix(Some(&*player))));
+        }
+    }
+
+    log::info!("Sleep timer ended");
+    SHUTDOWN.notify_one();
+}
+
+/// Warn when now playing info keeps failing to parse, instead of looking frozen with stale info.
+/// Warns again if it recovers and fails again.
+async fn watch_message_parse_failures() {
+    const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+    const WARNING_THRESHOLD: u32 = 3;
+
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+    let mut warned = false;
+    loop {
+        interval.tick().await;
+        let failures = code_radio_api::consecutive_parse_failures();
+        if failures >= WARNING_THRESHOLD && !warned {
+            log::warn!("{} now playing messages in a row failed to parse", failures);
+            print_above_progress_bar(&format!(
+                "{}",
+                "Now playing info failed to parse. The server's message format may have changed"
+                    .bright_yellow()
+            ));
+        }
+        warned = failures >= WARNING_THRESHOLD;
+    }
+}
+
+/// Tell users when the stream stalls, drops and reconnects,
+/// report streams which fail to start, like when switching stations,
+/// and exit if the player gives up reconnecting, instead of staying silent forever.
+async fn watch_player_state(
+    mut player_state: watch::Receiver<PlayerState>,
+    display_enabled: bool,
+    listener_metric: ListenerMetric,
+) {
+    while player_state.changed().await.is_ok() {
+        let current_state = player_state.borrow().clone();
+
+        let is_buffering = current_state == PlayerState::Buffering;
+        if STREAM_BUFFERING.swap(is_buffering, Ordering::Relaxed) != is_buffering {
+            let suffix = LATEST_MESSAGE
+                .lock()
+                .unwrap()
+                .as_ref()
+                .map(|m| get_progress_bar_suffix(&m.listeners, listener_metric));
+            if let Some(suffix) = suffix {
+                update_progress_bar(|p| p.set_message(suffix));
+            }
+        }
+
+        match current_state {
+            PlayerState::Reconnecting if display_enabled || tui::is_active() => {
+                print_above_progress_bar(&format!(
+                    "{}",
+                    "Stream dropped, reconnecting...".bright_yellow()
+                ));
+            }
+            // The player waits for the next station, so users can retry with R or switch with S
+            PlayerState::Failed(e) if display_enabled || tui::is_active() => {
+                print_above_progress_bar(&format!(
+                    "{}",
+                    format!("Failed to play: {}", e).bright_red()
+                ));
+            }
+            PlayerState::GaveUp(e) => {
+                update_progress_bar(ProgressBar::abandon);
+                PLAYER_GAVE_UP.lock().unwrap().replace(e);
+                SHUTDOWN.notify_one();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Return the player's error if it gave up reconnecting, so it exits with `ExitCode::Network`.
+fn check_player_gave_up() -> Result<()> {
+    match PLAYER_GAVE_UP.lock().unwrap().take() {
+        Some(e) => Err(anyhow!(e)).exit_code(ExitCode::Network),
+        None => Ok(()),
+    }
+}
+
+/// Print the server address and protocol the player is connected to.
+///
+/// Note: The HTTP client doesn't expose the negotiated TLS version, so it is not shown.
+fn print_connection_info() {
+    let connection_info = PLAYER
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(Player::connection_info);
+    match connection_info {
+        Some(connection_info) => {
+            let remote_addr = connection_info
+                .remote_addr
+                .map_or_else(|| "Unknown".to_owned(), |a| a.to_string());
+            println!(
+                "{} {} ({}, {:?})",
+                "Connection:".bright_green(),
+                remote_addr,
+                connection_info.protocol,
+                connection_info.http_version
+            );
+        }
+        None => println!("{} Not connected", "Connection:".bright_green()),
+    }
+}
+
+fn display_welcome_message(args: &Args) {
+    let logo = "
+ ██████╗ ██████╗ ██████╗ ███████╗    ██████╗  █████╗ ██████╗ ██╗ ██████╗ 
+██╔════╝██╔═══██╗██╔══██╗██╔════╝    ██╔══██╗██╔══██╗██╔══██╗██║██╔═══██╗
+██║     ██║   ██║██║  ██║█████╗      ██████╔╝███████║██║  ██║██║██║   ██║
+██║     ██║   ██║██║  ██║██╔══╝      ██╔══██╗██╔══██║██║  ██║██║██║   ██║
+╚██████╗╚██████╔╝██████╔╝███████╗    ██║  ██║██║  ██║██████╔╝██║╚██████╔╝
+ ╚═════╝ ╚═════╝ ╚═════╝ ╚══════╝    ╚═╝  ╚═╝╚═╝  ╚═╝╚═════╝ ╚═╝ ╚═════╝ ";
+
+    let app_name_and_version = format!("Code Radio CLI v{}", env!("CARGO_PKG_VERSION"));
+    let help_command = format!("{} --help", utils::get_current_executable_name());
+
+    // Keys are not read if stdin or stdout is not a terminal, so don't mention them
+    let keyboard_shortcuts_help = if terminal::can_read_keys() {
+        "Press 0-9 to adjust volume. Press Space to pause.
+Press ? to show all keyboard shortcuts.
+Press Q or Ctrl+C to exit.
+"
+    } else {
+        ""
+    };
+    let description = format!(
+        "{}
+A command line music radio client for https://coderadio.freecodecamp.org
+GitHub: https://github.com/JasonWei512/code-radio-cli
+
+{}Run {} to get more help.",
+        app_name_and_version.bright_green(),
+        keyboard_shortcuts_help,
+        help_command.bright_yellow()
+    );
+
+    if should_display_logo(logo, args) {
+        println!("{}", logo);
+        println!();
+    }
+    println!("{}", description);
+    println!();
+}
+
+/// The logo wraps into a garbled mess if the terminal is narrower than the logo,
+/// so skip it on narrow terminals unless `--force-logo` is passed.
+fn should_display_logo(logo: &str, args: &Args) -> bool {
+    if args.no_logo {
+        return false;
+    }
+    if args.force_logo {
+        return true;
+    }
+
+    let logo_width = logo
+        .lines()
+        .map(console::measure_text_width)
+        .max()
+        .unwrap_or_default();
+
+    match console::Term::stdout().size_checked() {
+        Some((_rows, columns)) => usize::from(columns) >= logo_width,
+        None => true,
+    }
+}
+
+/// Update progress bar's progress and listeners count suffix.
+///
+/// If song id changes, print the new song's info on screen.
+///
+/// Call this method when receiving a new message from Code Radio's Server-Sent Events stream.
+async fn update_song_info_on_screen(
+    message: CodeRadioMessage,
+    app_state: &mut AppState,
+    args: &Args,
+) {
+    LATEST_MESSAGE.lock().unwrap().replace(message.clone());
+    record_listener_count(get_listener_count(&message.listeners, args.listener_metric));
+
+    let mut is_new_song = false;
+    for event in app_state.on_message(message.clone()) {
+        match event {
+            UiEvent::LiveStarted { .. } | UiEvent::LiveEnded => {
+                announce_live_change(&message.live, args)
+            }
+            UiEvent::NewSong { is_first, .. } => {
+                is_new_song = true;
+                on_song_change(&message, is_first, args).await;
+            }
+            // Shown below, along with the progress bar
+            UiEvent::SongProgress { .. } | UiEvent::ListenersChanged(_) => {}
+        }
+    }
+
+    if args.json {
+        print_message_as_json(&message, args.listener_metric);
+        return;
+    }
+    if args.tui {
+        // The full-screen interface redraws from the latest message and song progress by itself
+        if is_new_song {
+            let song_progress = new_song_progress(&message.now_playing);
+            SONG_PROGRESS.lock().unwrap().replace(song_progress);
+        } else {
+            update_song_progress(|s| s.sync(&message.now_playing));
+        }
+        return;
+    }
+    if !args.is_display_enabled() {
+        return;
+    }
+
+    let song = &message.now_playing.song;
+
+    let total_seconds = message.now_playing.duration; // Note: This may be 0
+
+    let progress_bar_preffix = get_progress_bar_prefix(PLAYER.lock().unwrap().as_ref());
+    let progress_bar_suffix = get_progress_bar_suffix(&message.listeners, args.listener_metric);
+
+    if !is_new_song {
+        // Same song
+        let elapsed_seconds = update_song_progress(|s| {
+            s.sync(&message.now_playing);
+            s.elapsed_seconds()
+        });
+        update_progress_bar(|p| {
+            if let Some(elapsed_seconds) = elapsed_seconds {
+                p.set_position(elapsed_seconds);
+            }
+            p.set_message(progress_bar_suffix);
+        });
+    } else {
+        // New song
+        update_progress_bar(|p| p.finish_and_clear());
+
+        let song_progress = new_song_progress(&message.now_playing);
+        let elapsed_seconds = song_progress.elapsed_seconds();
+        SONG_PROGRESS.lock().unwrap().replace(song_progress);
+
+        println!();
+        if args.art && !song.art.is_empty() {
+            print_album_art(&song.art).await;
+        }
+
+        let title = utils::sanitize_metadata_text(&song.title);
+        let artist = utils::sanitize_metadata_text(&song.artist);
+        let album = utils::sanitize_metadata_text(&song.album);
+
+        let is_watched = is_watched_song(song, &args.watch);
+
+        // Long titles are elided instead of wrapping, to keep each line tidy
+        let mut lines = Vec::new();
+        if message.live.is_live {
+            lines.push(get_live_text(&message.live));
+        }
+        if let Some(format) = &args.format {
+            let now_playing_text = format_now_playing(format, &message, args.listener_metric);
+            if is_watched {
+                lines.push(format!("{}", now_playing_text.bright_magenta().bold()));
+            } else {
+                lines.push(now_playing_text);
+            }
+        } else {
+            if is_watched {
+                lines.push(format!(
+                    "{}       {}",
+                    "Song:".bright_magenta().bold(),
+                    title.bright_magenta().bold()
+                ));
+            } else {
+                lines.push(format!("{}       {}", "Song:".bright_green(), title));
+            }
+            lines.push(format!("{}     {}", "Artist:".bright_green(), artist));
+            lines.push(format!("{}      {}", "Album:".bright_green(), album));
+            if !song.genre.trim().is_empty() {
+                let genre = utils::sanitize_metadata_text(&song.genre);
+                lines.push(format!("{}      {}", "Genre:".bright_green(), genre));
+            }
+        }
+        for line in &lines {
+            println!("{}", terminal::fit_to_width(line));
+        }
+
+        if args.show_next {
+            print_next_song(&message.playing_next.song);
+        }
+
+        if args.prefetch_art {
+            let next_art_url = &message.playing_next.song.art;
+            *NEXT_ART_URL.lock().unwrap() =
+                (!next_art_url.is_empty()).then(|| next_art_url.clone());
+        }
+
+        if args.set_title {
+            terminal::set_title(&format!("{artist} - {title}"));
+        }
+
+        if let Some(art_file) = &args.art_file {
+            if !song.art.is_empty() {
+                let art_url = song.art.clone();
+                let art_file = art_file.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = album_art::download_to_file(&art_url, &art_file).await {
+                        log::warn!("Failed to write album art to file: {:#}", e);
+                    }
+                });
+            }
+        }
+
+        // A bar can't show progress without a duration, like during live broadcasts,
+        // so show a spinner with only elapsed time instead
+        let is_duration_known = total_seconds > 0;
+
+        // "Up next" already comes with a countdown, so don't show the remaining time twice
+        let progress_bar_template = if !is_duration_known {
+            "{prefix}  {spinner} {progress_info} - {msg}{sleep_timer}"
+        } else if args.show_next {
+            "{prefix}  {bar_color}{wide_bar}{bar_color_end} {progress_info} - {msg}{next_song_countdown}{sleep_timer}"
+        } else {
+            "{prefix}  {bar_color}{wide_bar}{bar_color_end} {progress_info} - {msg}{remaining_time}{sleep_timer}"
+        };
+
+        let progress_bar_style = ProgressStyle::with_template(progress_bar_template)
+            .unwrap()
+            .with_key(
+                "progress_info",
+                |state: &ProgressState, write: &mut dyn Write| {
+                    let progress_info = get_progress_bar_progress_info(state.pos(), state.len());
+                    write!(write, "{progress_info}").unwrap();
+                },
+            )
+            .with_key(
+                "next_song_countdown",
+                |state: &ProgressState, write: &mut dyn Write| {
+                    if let Some(countdown) = get_next_song_countdown(state.pos(), state.len()) {
+                        write!(write, " - Next in {countdown}").unwrap();
+                    }
+                },
+            )
+            .with_key(
+                "remaining_time",
+                |state: &ProgressState, write: &mut dyn Write| {
+                    if let Some(remaining_time) = get_next_song_countdown(state.pos(), state.len())
+                    {
+                        write!(write, " - {remaining_time} left").unwrap();
+                    }
+                },
+            )
+            .with_key("bar_color", write_bar_color)
+            .with_key("bar_color_end", write_bar_color_end)
+            .with_key("sleep_timer", write_sleep_timer);
+
+        let progress_bar = if is_duration_known {
+            ProgressBar::new(total_seconds as u64)
+        } else {
+            ProgressBar::new_spinner()
+        };
+        let progress_bar = progress_bar
+            .with_style(progress_bar_style)
+            .with_position(elapsed_seconds)
+            .with_prefix(progress_bar_preffix)
+            .with_message(progress_bar_suffix);
+
+        // Only song lines are printed if stdout is redirected, like to a file,
+        // which would otherwise be full of the bar's control characters
+        if STATION_PROMPT_OPEN.load(Ordering::SeqCst) || !terminal::is_stdout_terminal() {
+            progress_bar.set_draw_target(ProgressDrawTarget::hidden());
+        }
+        progress_bar.tick();
+        if !is_duration_known && args.refresh > 0 {
+            progress_bar.enable_steady_tick(LOADING_SPINNER_TICK_INTERVAL);
+        }
+
+        PROGRESS_BAR.lock().unwrap().replace(progress_bar);
+    }
+}
+
+/// The `{sleep_timer}` key of progress bar templates, like " - Sleep in 30m" when `--sleep` is set.
+fn write_sleep_timer(_state: &ProgressState, write: &mut dyn Write) {
+    if let Some(sleep_timer_end) = SLEEP_TIMER_END.get() {
+        let remaining = sleep_timer_end.saturating_duration_since(Instant::now());
+        write!(
+            write,
+            " - Sleep in {}",
+            get_sleep_timer_remaining_time(remaining)
+        )
+        .unwrap();
+    }
+}
+
+/// Start tracking a new song's progress, paused and behind the live stream like the current song,
+/// or paused if the player is, if it's the first song.
+fn new_song_progress(now_playing: &NowPlaying) -> SongProgress {
+    if let Some(song_progress) = SONG_PROGRESS.lock().unwrap().as_ref() {
+        return song_progress.next_song(now_playing);
+    }
+
+    let mut song_progress = SongProgress::new(SystemClock, now_playing);
+    if PLAYER
+        .lock()
+        .unwrap()
+        .as_ref()
+        .is_some_and(Player::is_paused)
+    {
+        song_progress.pause();
+    }
+    song_progress
+}
+
+/// Print now playing info as a single line of JSON, for piping into other tools like `jq`.
+fn print_message_as_json(message: &CodeRadioMessage, listener_metric: ListenerMetric) {
+    let song = &message.now_playing.song;
+    let json = serde_json::json!({
+        "station": message.station.name,
+        "title": song.title,
+        "artist": song.artist,
+        "album": song.album,
+        "elapsed": song_progress::get_elapsed_seconds(&message.now_playing, &SystemClock),
+        "duration": message.now_playing.duration,
+        "listeners": get_listener_count(&message.listeners, listener_metric),
+    });
+    println!("{json}");
+}
+
+/// Like `Volume 5/9`, or `No audio` if there's no player.
+fn get_progress_bar_prefix(player: Option<&Player>) -> String {
+    let player = match player {
+        Some(player) => player,
+        None => return "No audio".to_owned(),
+    };
+
+    let paused_indicator = if player.is_paused() { "PAUSED  " } else { "" };
+
+    if SHOW_VOLUME_PERCENT.load(Ordering::Relaxed) {
+        let volume_percentage = if player.is_muted() {
+            "muted".to_owned()
+        } else {
+            format!("{}%", get_volume_percentage(player.volume()))
+        };
+        return format!("{paused_indicator}Volume {volume_percentage}");
+    }
+
+    let volume_char = if player.is_muted() {
+        "M".to_owned()
+    } else {
+        player.volume().to_string()
+    };
+    format!("{paused_indicator}Volume {volume_char}/9")
+}
+
+/// Map a volume between 0 and 9 to a percentage, like 5 to 56.
+fn get_volume_percentage(volume: u8) -> u16 {
+    (u16::from(volume) * 100 + 4) / 9
+}
+
+/// Like `Listeners: 12 ▁▃▅█`, or `Buffering...` while the stream is stalled.
+fn get_progress_bar_suffix(listeners: &Listeners, listener_metric: ListenerMetric) -> String {
+    if STREAM_BUFFERING.load(Ordering::Relaxed) {
+        return "Buffering...".to_owned();
+    }
+    let listener_count = get_listener_count(listeners, listener_metric);
+    match get_listener_sparkline() {
+        Some(sparkline) => format!("Listeners: {listener_count} {sparkline}"),
+        None => format!("Listeners: {listener_count}"),
+    }
+}
+
+fn record_listener_count(listener_count: i64) {
+    let mut listener_history = LISTENER_HISTORY.lock().unwrap();
+    if listener_history.len() == LISTENER_HISTORY_LEN {
+        listener_history.pop_front();
+    }
+    listener_history.push_back(listener_count);
+}
+
+/// The most recent listener counts as a sparkline, as many as fit in the terminal.
+/// `None` if there are too few counts to show a trend, or no room for them.
+fn get_listener_sparkline() -> Option<String> {
+    let width = match console::Term::stdout().size_checked() {
+        Some((_rows, columns)) => {
+            usize::from(columns).saturating_sub(LISTENER_SPARKLINE_RESERVED_COLUMNS)
+        }
+        None => LISTENER_HISTORY_LEN,
+    };
+
+    let listener_history = LISTENER_HISTORY.lock().unwrap();
+    let listener_counts: Vec<i64> = listener_history
+        .iter()
+        .skip(listener_history.len().saturating_sub(width))
+        .copied()
+        .collect();
+    (listener_counts.len() >= 2).then(|| utils::render_sparkline(&listener_counts))
+}
+
+const fn get_listener_count(listeners: &Listeners, listener_metric: ListenerMetric) -> i64 {
+    match listener_metric {
+        ListenerMetric::Current => listeners.current,
+        ListenerMetric::Unique => listeners.unique,
+        ListenerMetric::Total => listeners.total,
+    }
+}
+
+/// Like `12m`, or `45s` in the last minute.
+fn get_sleep_timer_remaining_time(remaining: Duration) -> String {
+    let remaining_seconds = remaining.as_secs();
+    if remaining_seconds >= 60 {
+        format!("{}m", remaining_seconds.div_ceil(60))
+    } else {
+        format!("{remaining_seconds}s")
+    }
+}
+
+/// The `{bar_color}` key of progress bar templates, which colors the bar up to `{bar_color_end}`:
+/// green, or yellow when the song is about to end. No color if colors are disabled.
+fn write_bar_color(state: &ProgressState, write: &mut dyn Write) {
+    const GREEN: &str = "\x1b[32m";
+    const YELLOW: &str = "\x1b[33m";
+
+    if !console::colors_enabled() {
+        return;
+    }
+    let color = if is_song_ending(state.pos(), state.len()) {
+        YELLOW
+    } else {
+        GREEN
+    };
+    write!(write, "{color}").unwrap();
+}
+
+fn write_bar_color_end(_state: &ProgressState, write: &mut dyn Write) {
+    if console::colors_enabled() {
+        write!(write, "\x1b[0m").unwrap();
+    }
+}
+
+/// Whether the song ends within `SONG_ENDING_SECONDS`. `false` if its duration is unknown.
+fn is_song_ending(elapsed_seconds: u64, total_seconds: Option<u64>) -> bool {
+    total_seconds.is_some_and(|total_seconds| {
+        total_seconds.saturating_sub(elapsed_seconds) <= SONG_ENDING_SECONDS
+    })
+}
+
+/// Time until the next song starts, like `03:12`.
+/// `None` if the current song's duration is unknown, or it has already run out.
+fn get_next_song_countdown(elapsed_seconds: u64, total_seconds: Option<u64>) -> Option<String> {
+    match total_seconds {
+        Some(total_seconds) if total_seconds > elapsed_seconds => Some(
+            utils::humanize_seconds_to_minutes_and_seconds(total_seconds - elapsed_seconds),
+        ),
+        _ => None,
+    }
+}
+
+/// - If `elapsed_seconds` and `total_seconds` are both known:
+///
+///   `01:14 / 05:14`
+///
+/// - If `elapsed_seconds` is known but `total_seconds` is unknown:
+///
+///   `01:14`
+fn get_progress_bar_progress_info(elapsed_seconds: u64, total_seconds: Option<u64>) -> String {
+    let humanized_elapsed_duration =
+        utils::humanize_seconds_to_minutes_and_seconds(elapsed_seconds);
+
+    if let Some(total_seconds) = total_seconds {
+        let humanized_total_duration =
+            utils::humanize_seconds_to_minutes_and_seconds(total_seconds);
+        return format!("{humanized_elapsed_duration} / {humanized_total_duration}");
+    }
+
+    humanized_elapsed_duration
+}
+
+/// Update elapsed seconds in progress bar every `--refresh` milliseconds, unless it's 0.
+fn start_ticking_progress_bar(args: &Args) {
+    if args.refresh > 0 {
+        tokio::spawn(tick_progress_bar_progress(Duration::from_millis(
+            args.refresh,
+        )));
+    }
+}
+
+async fn tick_progress_bar_progress(refresh_interval: Duration) {
+    let mut interval = tokio::time::interval(refresh_interval);
+    loop {
+        interval.tick().await;
+        if let Some(elapsed_seconds) = update_song_progress(|s| s.elapsed_seconds()) {
+            update_progress_bar(|p| p.set_position(elapsed_seconds));
+            prefetch_next_art_if_song_ending(elapsed_seconds);
+        }
+    }
+}
+
+/// Download the next song's album art once the current song ends within `PREFETCH_ART_SECONDS`,
+/// so it's cached by the time the song changes.
+fn prefetch_next_art_if_song_ending(elapsed_seconds: u64) {
+    let total_seconds = LATEST_MESSAGE
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map_or(0, |message| message.now_playing.duration.max(0) as u64);
+    if total_seconds == 0 || total_seconds.saturating_sub(elapsed_seconds) > PREFETCH_ART_SECONDS {
+        return;
+    }
+
+    if let Some(next_art_url) = NEXT_ART_URL.lock().unwrap().take() {
+        tokio::spawn(album_art::prefetch(next_art_url));
+    }
+}
+
+fn update_song_progress<T, R>(action: T) -> Option<R>
+where
+    T: FnOnce(&mut SongProgress) -> R,
+{
+    SONG_PROGRESS.lock().unwrap().as_mut().map(action)
+}
+
+fn update_progress_bar<T>(action: T)
+where
+    T: FnOnce(&ProgressBar),
+{
+    if let Some(progress_bar) = PROGRESS_BAR.lock().unwrap().as_ref() {
+        action(progress_bar);
     }
 }
 
-async fn start() -> Result<()> {
-    let args = Args::parse();
+#[derive(Clone, Copy)]
+enum KeyAction {
+    Volume,
+    Mute,
+    Pause,
+    SongHistory,
+    Lyrics,
+    CopySong,
+    SwitchStation,
+    NextFavorite,
+    Favorite,
+    VolumePreset,
+    Reconnect,
+    Help,
+    Quit,
+}
 
-    if args.volume > 9 {
-        return Err(anyhow!("Volume must be between 0 and 9"));
-    }
+struct KeyboardShortcut {
+    keys: &'static [char],
+    /// How the keys are shown in the help text
+    label: &'static str,
+    description: &'static str,
+    action: KeyAction,
+}
 
-    start_playing(args).await?;
+/// All keyboard shortcuts, for both handling keys and showing the help text.
+const KEYBOARD_SHORTCUTS: &[KeyboardShortcut] = &[
+    KeyboardShortcut {
+        keys: &['0', '1', '2', '3', '4', '5', '6', '7', '8', '9'],
+        label: "0-9",
+        description: "Adjust volume (also unmutes)",
+        action: KeyAction::Volume,
+    },
+    KeyboardShortcut {
+        keys: &['m', 'M'],
+        label: "M",
+        description: "Mute or unmute",
+        action: KeyAction::Mute,
+    },
+    KeyboardShortcut {
+        keys: &['p', 'P'],
+        label: "P",
+        description: "Switch to the next volume preset",
+        action: KeyAction::VolumePreset,
+    },
+    KeyboardShortcut {
+        keys: &[' '],
+        label: "Space",
+        description: "Pause or resume",
+        action: KeyAction::Pause,
+    },
+    KeyboardShortcut {
+        keys: &['h', 'H'],
+        label: "H",
+        description: "Show recently played songs",
+        action: KeyAction::SongHistory,
+    },
+    KeyboardShortcut {
+        keys: &['l', 'L'],
+        label: "L",
+        description: "Show lyrics",
+        action: KeyAction::Lyrics,
+    },
+    KeyboardShortcut {
+        keys: &['c', 'C'],
+        label: "C",
+        description: "Copy current song to clipboard",
+        action: KeyAction::CopySong,
+    },
+    KeyboardShortcut {
+        keys: &['s', 'S'],
+        label: "S",
+        description: "Switch station",
+        action: KeyAction::SwitchStation,
+    },
+    KeyboardShortcut {
+        keys: &['f', 'F'],
+        label: "F",
+        description: "Switch to the next favorite station",
+        action: KeyAction::NextFavorite,
+    },
+    KeyboardShortcut {
+        keys: &[],
+        label: "Alt+1-9",
+        description: "Switch to favorite station 1-9",
+        action: KeyAction::Favorite,
+    },
+    KeyboardShortcut {
+        keys: &['r', 'R'],
+        label: "R",
+        description: "Reconnect to the stream, like when it gets stuck",
+        action: KeyAction::Reconnect,
+    },
+    KeyboardShortcut {
+        keys: &['?'],
+        label: "?",
+        description: "Show keyboard shortcuts",
+        action: KeyAction::Help,
+    },
+    KeyboardShortcut {
+        keys: &['q', 'Q', '\x1b'],
+        label: "Q/Esc",
+        description: "Quit",
+        action: KeyAction::Quit,
+    },
+];
 
-    Ok(())
+/// Handle keyboard input according to `KEYBOARD_SHORTCUTS`.
+fn handle_keyboard_input(runtime: &tokio::runtime::Handle) {
+    loop {
+        let c = match terminal::read_key() {
+            Ok(console::Key::Char(c)) => c,
+            Ok(console::Key::Escape) => '\x1b',
+            Ok(console::Key::UnknownEscSeq(sequence)) => {
+                if let [digit @ '1'..='9'] = sequence.as_slice() {
+                    switch_to_favorite(digit.to_digit(10).unwrap_or_default() as usize, runtime);
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        let shortcut = match KEYBOARD_SHORTCUTS.iter().find(|s| s.keys.contains(&c)) {
+            Some(shortcut) => shortcut,
+            None => continue,
+        };
+
+        match shortcut.action {
+            KeyAction::Volume => {
+                if let Some(n) = c.to_digit(10) {
+                    set_volume(n as u8);
+                }
+            }
+            KeyAction::Mute => toggle_mute(),
+            KeyAction::VolumePreset => switch_to_next_volume_preset(),
+            KeyAction::Pause => toggle_pause(),
+            KeyAction::SongHistory => print_song_history(),
+            KeyAction::Lyrics => print_lyrics(),
+            KeyAction::CopySong => copy_current_song(),
+            KeyAction::SwitchStation => switch_station_interactively(runtime),
+            KeyAction::NextFavorite => {
+                switch_to_next_favorite(runtime);
+            }
+            // Handled above, since Alt+digit isn't a character
+            KeyAction::Favorite => {}
+            KeyAction::Reconnect => reconnect_stream(),
+            KeyAction::Help => print_keyboard_shortcuts(),
+            // Stop reading keys, so the terminal stays in "canonical" mode while quitting.
+            // See the comments in "terminal" module.
+            KeyAction::Quit => {
+                SHUTDOWN.notify_one();
+                return;
+            }
+        }
+    }
 }
 
-async fn start_playing(args: Args) -> Result<()> {
-    // Check update in background
-    let update_checking_task = tokio::spawn(update_checker::get_new_release());
+fn print_keyboard_shortcuts() {
+    let mut lines = vec![format!("{}", "Keyboard shortcuts:".bright_green())];
+    lines.extend(
+        get_keyboard_shortcut_lines()
+            .into_iter()
+            .map(|l| format!("  {l}")),
+    );
+    print_above_progress_bar(&lines.join("\n"));
+}
 
-    display_welcome_message(&args);
+/// Like `0-9    Adjust volume (also unmutes)`, one line for each shortcut.
+fn get_keyboard_shortcut_lines() -> Vec<String> {
+    let label_width = KEYBOARD_SHORTCUTS
+        .iter()
+        .map(|s| s.label.len())
+        .max()
+        .unwrap_or(0);
 
-    let selected_station: Option<Remote> = if args.select_station {
-        let station = select_station_interactively().await?;
-        Some(station)
-    } else {
-        None
-    };
+    KEYBOARD_SHORTCUTS
+        .iter()
+        .map(|s| {
+            format!(
+                "{:<width$}  {}",
+                s.label,
+                s.description,
+                width = label_width
+            )
+        })
+        .collect()
+}
 
-    // Fetching data in background while creating `Player` to improve startup speed
-    // Note: Here we use the REST API to get the first API message,
-    // because getting the first message from the Server-Sent Events stream may be slow
-    let get_message_task = tokio::spawn(code_radio_api::get_message());
-    let mut message_stream = code_radio_api::get_message_stream();
+/// Switch between `--volume-preset`s in the order they were given, wrapping around.
+fn switch_to_next_volume_preset() {
+    let presets = VOLUME_PRESETS.get().map_or(&[][..], Vec::as_slice);
+    if presets.is_empty() {
+        print_above_progress_bar("No volume presets. Add one with --volume-preset <NAME=VOLUME>");
+        return;
+    }
 
-    let loading_spinner = ProgressBar::new_spinner()
-        .with_style(ProgressStyle::with_template("{spinner} {msg}")?)
-        .with_message("Initializing audio device...");
-    loading_spinner.enable_steady_tick(LOADING_SPINNER_TICK_INTERVAL);
+    let index = NEXT_VOLUME_PRESET.fetch_add(1, Ordering::Relaxed) % presets.len();
+    let preset = &presets[index];
+    set_volume(preset.volume.get());
+    print_above_progress_bar(&format!(
+        "{}    {}",
+        "Volume preset:".bright_green(),
+        preset.name
+    ));
+}
 
-    // Creating a `Player` might be time consuming. It might take several seconds on first run.
-    match Player::try_new() {
-        Ok(mut player) => {
-            player.set_volume(args.volume);
-            PLAYER.lock().unwrap().replace(player);
-        }
-        Err(e) => {
-            terminal::print_error(e);
-            println!();
+fn set_volume(volume: u8) {
+    if let Some(player) = PLAYER.lock().unwrap().as_mut() {
+        if player.volume() == volume && !player.is_muted() {
+            return;
         }
+        player.set_volume(volume);
+        persisted_state::save_volume(player.volume());
+        update_progress_bar(|p| p.set_prefix(get_progress_bar_prefix(Some(&*player))));
     }
+}
 
-    loading_spinner.set_message("Connecting...");
-    let message = get_message_task.await??;
-    loading_spinner.finish_and_clear();
+/// Download and render album art in the terminal.
+///
+/// Skip silently if the download takes too long or fails, colors are disabled,
+/// or the terminal width is unknown.
+async fn print_album_art(art_url: &str) {
+    const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(5);
+    const MAX_ART_WIDTH: u16 = 32;
 
-    let stations = code_radio_api::get_stations_from_message(&message);
+    if !colored::control::SHOULD_COLORIZE.should_colorize() {
+        return;
+    }
+    let columns = match console::Term::stdout().size_checked() {
+        Some((_rows, columns)) => columns,
+        None => return,
+    };
 
-    let listen_url = match selected_station {
-        Some(ref station) => stations
-            .iter()
-            .find(|s| s.id == station.id)
-            .context(anyhow!("Station with ID \"{}\" not found", station.id))?
-            .url
-            .clone(),
-        None => message.station.listen_url.clone(),
+    let image = match tokio::time::timeout(DOWNLOAD_TIMEOUT, album_art::download(art_url)).await {
+        Ok(Ok(image)) => image,
+        Ok(Err(e)) => {
+            log::warn!("Failed to download album art: {:#}", e);
+            return;
+        }
+        Err(_) => {
+            log::warn!("Timed out downloading album art");
+            return;
+        }
     };
 
-    // Notify user if a new version is available
-    if update_checking_task.is_finished() {
-        if let Ok(Ok(Some(new_release))) = update_checking_task.await {
-            println!(
-                "{}",
-                format!("New version available: {}", new_release.version).bright_yellow()
-            );
-            println!("{}", new_release.url.bright_yellow());
-            println!();
+    match album_art::render_as_half_blocks(&image, u32::from(columns.min(MAX_ART_WIDTH))) {
+        Ok(art) => print!("{}", art),
+        Err(e) => log::warn!("Failed to render album art: {:#}", e),
+    }
+}
+
+/// Run everything that should happen once per song, besides showing it on screen.
+async fn on_song_change(message: &CodeRadioMessage, is_first: bool, args: &Args) {
+    media_controls::set_now_playing(&message.now_playing);
+
+    if args.record_split {
+        if let Some(player) = PLAYER.lock().unwrap().as_ref() {
+            let song = &message.now_playing.song;
+            player.split_recording(&song.artist, &song.title);
         }
     }
 
-    if let Some(station) = stations.iter().find(|station| station.url == listen_url) {
-        println!("{}    {}", "Station:".bright_green(), station.name);
+    if let Some(now_playing_file) = &args.now_playing_file {
+        let now_playing_format = args
+            .now_playing_format
+            .as_deref()
+            .or(args.format.as_deref())
+            .unwrap_or(DEFAULT_NOW_PLAYING_FORMAT);
+        let now_playing_text =
+            format_now_playing(now_playing_format, message, args.listener_metric);
+        if let Err(e) = tokio::fs::write(now_playing_file, now_playing_text).await {
+            log::warn!("Failed to write now playing file: {:#}", e);
+        }
     }
 
-    if let Some(player) = PLAYER.lock().unwrap().as_ref() {
-        player.play(&listen_url);
+    if let Some(command) = &args.on_song_change {
+        song_change_command::run(command, &message.now_playing.song);
     }
 
-    let mut last_song_id = String::new();
-    update_song_info_on_screen(message, &mut last_song_id);
-    tokio::spawn(tick_progress_bar_progress());
-    thread::spawn(handle_keyboard_input);
+    if let Some(webhook_url) = &args.webhook {
+        webhook::post_song_change(
+            webhook_url,
+            webhook::SongChangePayload::new(
+                &message.station.name,
+                &message.now_playing.song,
+                get_listener_count(&message.listeners, args.listener_metric),
+            ),
+        );
+    }
 
-    while let Some(message) = message_stream.next().await {
-        update_song_info_on_screen(message?, &mut last_song_id);
+    // The first song is already on screen right after launching, so notifying it is just noise
+    if args.notify && !is_first {
+        notification::show_song_notification(&message.now_playing.song);
     }
 
-    Err(anyhow!("Server-Sent Events connection was closed"))
+    if is_watched_song(&message.now_playing.song, &args.watch)
+        && (args.is_display_enabled() || args.tui)
+    {
+        terminal::ring_bell();
+    }
 }
 
-fn display_welcome_message(args: &Args) {
-    let logo = "
- ██████╗ ██████╗ ██████╗ ███████╗    ██████╗  █████╗ ██████╗ ██╗ ██████╗ 
-██╔════╝██╔═══██╗██╔══██╗██╔════╝    ██╔══██╗██╔══██╗██╔══██╗██║██╔═══██╗
-██║     ██║   ██║██║  ██║█████╗      ██████╔╝███████║██║  ██║██║██║   ██║
-██║     ██║   ██║██║  ██║██╔══╝      ██╔══██╗██╔══██║██║  ██║██║██║   ██║
-╚██████╗╚██████╔╝██████╔╝███████╗    ██║  ██║██║  ██║██████╔╝██║╚██████╔╝
- ╚═════╝ ╚═════╝ ╚═════╝ ╚══════╝    ╚═╝  ╚═╝╚═╝  ╚═╝╚═════╝ ╚═╝ ╚═════╝ ";
+/// Like `LIVE: DJ Name`, highlighted.
+fn get_live_text(live: &Live) -> String {
+    let streamer_name = utils::sanitize_metadata_text(&live.streamer_name);
+    let live_text = if streamer_name.is_empty() {
+        "LIVE".to_owned()
+    } else {
+        format!("LIVE: {streamer_name}")
+    };
+    format!("{}", live_text.bright_red().bold())
+}
 
-    let app_name_and_version = format!("Code Radio CLI v{}", env!("CARGO_PKG_VERSION"));
-    let help_command = format!("{} --help", utils::get_current_executable_name());
+/// Announce that a live broadcast started or ended.
+fn announce_live_change(live: &Live, args: &Args) {
+    if live.is_live {
+        log::info!("Live broadcast started by {}", live.streamer_name);
+    } else {
+        log::info!("Live broadcast ended");
+    }
 
-    let description = format!(
-        "{}
-A command line music radio client for https://coderadio.freecodecamp.org
-GitHub: https://github.com/JasonWei512/code-radio-cli
+    if !args.is_display_enabled() && !args.tui {
+        return;
+    }
+    if live.is_live {
+        print_above_progress_bar(&get_live_text(live));
+    } else {
+        print_above_progress_bar(&format!("{}", "Live broadcast ended".bright_yellow()));
+    }
+}
 
-Press 0-9 to adjust volume. Press Ctrl+C to exit.
-Run {} to get more help.",
-        app_name_and_version.bright_green(),
-        help_command.bright_yellow()
+/// Whether the song's title or artist contains any of the `--watch` terms, ignoring case.
+fn is_watched_song(song: &Song, watch_terms: &[String]) -> bool {
+    let title = song.title.to_lowercase();
+    let artist = song.artist.to_lowercase();
+    watch_terms.iter().any(|term| {
+        let term = term.to_lowercase();
+        !term.is_empty() && (title.contains(&term) || artist.contains(&term))
+    })
+}
+
+/// Replace placeholders like `{title}` and `{artist}` in `format` with the current song's info.
+/// See `--format` for all placeholders.
+fn format_now_playing(
+    format: &str,
+    message: &CodeRadioMessage,
+    listener_metric: ListenerMetric,
+) -> String {
+    let now_playing = &message.now_playing;
+    let song = &now_playing.song;
+
+    let title = utils::sanitize_metadata_text(&song.title);
+    let artist = utils::sanitize_metadata_text(&song.artist);
+    let album = utils::sanitize_metadata_text(&song.album);
+    let genre = utils::sanitize_metadata_text(&song.genre);
+    let listeners = get_listener_count(&message.listeners, listener_metric).to_string();
+    let elapsed = utils::humanize_seconds_to_minutes_and_seconds(
+        song_progress::get_elapsed_seconds(now_playing, &SystemClock),
     );
+    let duration =
+        utils::humanize_seconds_to_minutes_and_seconds(now_playing.duration.max(0) as u64);
 
-    if !args.no_logo {
-        println!("{}", logo);
-        println!();
+    utils::render_template(
+        format,
+        &[
+            ("title", &title),
+            ("artist", &artist),
+            ("album", &album),
+            ("genre", &genre),
+            ("listeners", &listeners),
+            ("elapsed", &elapsed),
+            ("duration", &duration),
+        ],
+    )
+}
+
+fn print_next_song(next_song: &Song) {
+    let next_title = utils::sanitize_metadata_text(next_song.title.trim());
+    let next_artist = utils::sanitize_metadata_text(next_song.artist.trim());
+
+    let next_song_text = match (next_artist.is_empty(), next_title.is_empty()) {
+        (_, true) => return,
+        (true, false) => next_title,
+        (false, false) => format!("{next_artist} - {next_title}"),
+    };
+
+    let line = format!("{}", format!("Up next:    {next_song_text}").dimmed());
+    println!("{}", terminal::fit_to_width(&line));
+}
+
+fn print_song_history() {
+    let song_history = match LATEST_MESSAGE.lock().unwrap().as_ref() {
+        Some(message) => message.song_history.clone(),
+        None => return,
+    };
+
+    let mut lines = vec![format!("{}", "Recently played:".bright_green())];
+    if song_history.is_empty() {
+        lines.push("  No song history available.".to_owned());
     }
-    println!("{}", description);
-    println!();
+    for (i, history) in song_history.iter().enumerate() {
+        lines.push(format!(
+            "{:>3}. {} - {}",
+            i + 1,
+            utils::sanitize_metadata_text(&history.song.artist),
+            utils::sanitize_metadata_text(&history.song.title)
+        ));
+    }
+
+    print_above_progress_bar(&lines.join("\n"));
 }
 
-/// Update progress bar's progress and listeners count suffix.
-///
-/// If song id changes, print the new song's info on screen.
-///
-/// Call this method when receiving a new message from Code Radio's Server-Sent Events stream.
-fn update_song_info_on_screen(message: CodeRadioMessage, last_song_id: &mut String) {
-    let song = message.now_playing.song;
+fn print_lyrics() {
+    let lyrics = match LATEST_MESSAGE.lock().unwrap().as_ref() {
+        Some(message) => message.now_playing.song.lyrics.clone(),
+        None => return,
+    };
 
-    let elapsed_seconds = message.now_playing.elapsed;
-    let total_seconds = message.now_playing.duration; // Note: This may be 0
+    let mut lines = vec![format!("{}", "Lyrics:".bright_green())];
+    if lyrics.trim().is_empty() {
+        lines.push("  No lyrics available.".to_owned());
+    }
+    for line in lyrics.trim().lines() {
+        lines.push(format!("  {}", utils::sanitize_metadata_text(line)));
+    }
+
+    print_above_progress_bar(&lines.join("\n"));
+}
 
-    let progress_bar_preffix =
-        get_progress_bar_prefix(PLAYER.lock().unwrap().as_ref().map(Player::volume));
-    let progress_bar_suffix = get_progress_bar_suffix(message.listeners.current);
+/// Copy "artist - title" of current song to the clipboard.
+fn copy_current_song() {
+    let text = match LATEST_MESSAGE.lock().unwrap().as_ref() {
+        Some(message) => format!(
+            "{} - {}",
+            utils::sanitize_metadata_text(&message.now_playing.song.artist),
+            utils::sanitize_metadata_text(&message.now_playing.song.title)
+        ),
+        None => return,
+    };
 
-    if song.id == *last_song_id {
-        // Same song
-        update_progress_bar(|p| {
-            p.set_position(elapsed_seconds as u64);
-            p.set_message(progress_bar_suffix);
-        });
-    } else {
-        // New song
-        update_progress_bar(|p| p.finish_and_clear());
+    match clipboard::copy_text(text.clone()) {
+        Ok(()) => print_above_progress_bar(&format!("Copied \"{}\" to clipboard", text)),
+        Err(e) => {
+            log::warn!("Failed to copy to clipboard: {:#}", e);
+            print_above_progress_bar(&format!("Failed to copy to clipboard: {:#}", e));
+        }
+    }
+}
 
-        *last_song_id = song.id.clone();
+/// Print a line of `--verbose` logs to stderr, without disrupting the progress bar.
+fn print_log_line(line: &str) {
+    if tui::is_active() {
+        return; // It would mess up the full-screen interface
+    }
 
-        println!();
-        println!("{}       {}", "Song:".bright_green(), song.title);
-        println!("{}     {}", "Artist:".bright_green(), song.artist);
-        println!("{}      {}", "Album:".bright_green(), song.album);
+    // Logs may come while the progress bar is locked, like when updating it
+    let progress_bar = PROGRESS_BAR.try_lock().ok().and_then(|p| p.clone());
+    match progress_bar {
+        Some(progress_bar) => progress_bar.suspend(|| eprintln!("{line}")),
+        None => eprintln!("{line}"),
+    }
+}
 
-        let progress_bar_len = if total_seconds > 0 {
-            total_seconds as u64
-        } else {
-            u64::MAX
-        };
+/// Print text without disrupting the progress bar, which will be redrawn below the text.
+///
+/// In the full-screen interface, show it in the status line instead.
+fn print_above_progress_bar(text: &str) {
+    if tui::is_active() {
+        tui::set_status(text);
+        return;
+    }
 
-        let progress_bar_style =
-            ProgressStyle::with_template("{prefix}  {wide_bar} {progress_info} - {msg}")
-                .unwrap()
-                .with_key(
-                    "progress_info",
-                    |state: &ProgressState, write: &mut dyn Write| {
-                        let progress_info =
-                            get_progress_bar_progress_info(state.pos(), state.len());
-                        write!(write, "{progress_info}").unwrap();
-                    },
-                );
+    match PROGRESS_BAR.lock().unwrap().as_ref() {
+        Some(progress_bar) => progress_bar.suspend(|| {
+            println!();
+            println!("{text}");
+        }),
+        None => {
+            println!();
+            println!("{text}");
+        }
+    }
+}
 
-        let progress_bar = ProgressBar::new(progress_bar_len)
-            .with_style(progress_bar_style)
-            .with_position(elapsed_seconds as u64)
-            .with_prefix(progress_bar_preffix)
-            .with_message(progress_bar_suffix);
+fn toggle_mute() {
+    if let Some(player) = PLAYER.lock().unwrap().as_mut() {
+        player.toggle_mute();
+        update_progress_bar(|p| p.set_prefix(get_progress_bar_prefix(Some(&*player))));
+    }
+}
 
-        progress_bar.tick();
+fn toggle_pause() {
+    if let Some(player) = PLAYER.lock().unwrap().as_mut() {
+        let resume_live = player.is_paused() && RESUME_LIVE.load(Ordering::Relaxed);
+        let paused = if resume_live {
+            player.reconnect();
+            false
+        } else {
+            player.toggle_pause()
+        };
+        media_controls::set_paused(paused);
+        update_song_progress(|s| {
+            if paused {
+                s.pause();
+            } else if resume_live {
+                s.resume_live();
+            } else {
+                s.resume();
+            }
+        });
+        update_progress_bar(|p| p.set_prefix(get_progress_bar_prefix(Some(&*player))));
+    }
+}
 
-        PROGRESS_BAR.lock().unwrap().replace(progress_bar);
+fn reconnect_stream() {
+    if let Some(player) = PLAYER.lock().unwrap().as_mut() {
+        if !player.reconnect() {
+            return;
+        }
+        log::info!("Reconnecting to the stream by user");
+        media_controls::set_paused(false);
+        update_song_progress(SongProgress::resume_live);
+        update_progress_bar(|p| p.set_prefix(get_progress_bar_prefix(Some(&*player))));
+        print_above_progress_bar(&format!("{}", "Reconnecting...".bright_yellow()));
     }
 }
 
-fn get_progress_bar_prefix(volume: Option<u8>) -> String {
-    let volume_char = volume.map_or_else(|| "*".to_owned(), |v| v.to_string());
-    format!("Volume {volume_char}/9")
+fn set_paused(paused: bool) {
+    let is_paused = PLAYER
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map_or(paused, Player::is_paused);
+    if is_paused != paused {
+        toggle_pause();
+    }
 }
 
-fn get_progress_bar_suffix(listener_count: i64) -> String {
-    format!("Listeners: {listener_count}")
+/// A live stream can't be stopped and resumed where it left off, so stopping just pauses it.
+fn handle_media_command(command: MediaCommand) {
+    match command {
+        MediaCommand::Play => set_paused(false),
+        MediaCommand::Pause | MediaCommand::Stop => set_paused(true),
+        MediaCommand::PlayPause => toggle_pause(),
+    }
 }
 
-/// - If `elapsed_seconds` and `total_seconds` are both known:
-///
-///   `01:14 / 05:14`
-///
-/// - If `elapsed_seconds` is known but `total_seconds` is unknown:
+/// Handle keyboard shortcuts on a new thread, if keys can be read.
+fn spawn_keyboard_input_handler() {
+    if !terminal::can_read_keys() {
+        log::info!("Not reading keys, since stdin or stdout is not a terminal");
+        return;
+    }
+    let runtime = tokio::runtime::Handle::current();
+    thread::spawn(move || handle_keyboard_input(&runtime));
+}
+
+/// Switch to a station selected with the same prompt as `--select-station`, without restarting.
 ///
-///   `01:14`
-fn get_progress_bar_progress_info(elapsed_seconds: u64, total_seconds: Option<u64>) -> String {
-    let humanized_elapsed_duration =
-        utils::humanize_seconds_to_minutes_and_seconds(elapsed_seconds);
+/// This runs on the keyboard input thread, so the prompt is the only one reading keys.
+fn switch_station_interactively(runtime: &tokio::runtime::Handle) {
+    let stations = match LATEST_MESSAGE.lock().unwrap().as_ref() {
+        Some(message) => code_radio_api::get_stations_from_message(message),
+        None => return,
+    };
 
-    if let Some(total_seconds) = total_seconds {
-        if total_seconds != u64::MAX {
-            let humanized_total_duration =
-                utils::humanize_seconds_to_minutes_and_seconds(total_seconds);
-            return format!("{humanized_elapsed_duration} / {humanized_total_duration}");
+    STATION_PROMPT_OPEN.store(true, Ordering::SeqCst);
+    update_progress_bar(|p| p.set_draw_target(ProgressDrawTarget::hidden()));
+    println!();
+    let selected_station = prompt_station(stations);
+    STATION_PROMPT_OPEN.store(false, Ordering::SeqCst);
+    update_progress_bar(|p| p.set_draw_target(ProgressDrawTarget::stderr()));
+
+    match selected_station {
+        Ok(Some(station)) => switch_station(station, runtime),
+        Ok(None) => {} // User cancelled the prompt
+        Err(e) => {
+            log::error!("Failed to select station: {:#}", e);
+            print_above_progress_bar(&format!("Failed to select station: {:#}", e));
         }
     }
+}
 
-    humanized_elapsed_duration
+/// Switch to the favorite station after the current one, skipping favorites which no longer exist.
+/// Returns the name of the station switched to.
+fn switch_to_next_favorite(runtime: &tokio::runtime::Handle) -> Option<String> {
+    let favorite_stations = get_favorite_stations(runtime);
+    let current_url = PLAYER
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|player| player.current_stream_url().map(str::to_owned));
+    let favorites: Vec<&Remote> = favorite_stations.iter().collect();
+    match favorites::get_next(&favorites, current_url.as_deref()) {
+        Some(station) => Some(switch_to_favorite_station(station.clone(), runtime)),
+        None => {
+            print_above_progress_bar(
+                "No favorite stations. Add one with --favorite <NAME_OR_SHORTCODE>",
+            );
+            None
+        }
+    }
 }
 
-/// Increase elapsed seconds in progress bar by 1 every second.
-async fn tick_progress_bar_progress() {
-    let mut interval = tokio::time::interval(Duration::from_secs(1));
-    loop {
-        interval.tick().await;
-        update_progress_bar(|p| p.inc(1));
+/// Switch to the `number`th favorite station, counting from 1, in the order of `--favorites`.
+/// Returns the name of the station switched to.
+fn switch_to_favorite(number: usize, runtime: &tokio::runtime::Handle) -> Option<String> {
+    let favorite_stations = get_favorite_stations(runtime);
+    match number
+        .checked_sub(1)
+        .and_then(|index| favorite_stations.get(index))
+    {
+        Some(station) => Some(switch_to_favorite_station(station.clone(), runtime)),
+        None => {
+            print_above_progress_bar(&format!(
+                "No favorite station {}. Add one with --favorite <NAME_OR_SHORTCODE>",
+                number
+            ));
+            None
+        }
     }
 }
 
-fn update_progress_bar<T>(action: T)
-where
-    T: FnOnce(&ProgressBar),
-{
-    if let Some(progress_bar) = PROGRESS_BAR.lock().unwrap().as_ref() {
-        action(progress_bar);
+/// The favorite stations which still exist, in the order they were added.
+fn get_favorite_stations(runtime: &tokio::runtime::Handle) -> Vec<Remote> {
+    let stations = match LATEST_MESSAGE.lock().unwrap().as_ref() {
+        Some(message) => code_radio_api::get_stations_from_message(message),
+        None => return Vec::new(),
+    };
+    let favorite_ids = runtime.block_on(favorites::load_ids());
+    favorites::resolve(&favorite_ids, &stations)
+        .into_iter()
+        .cloned()
+        .collect()
+}
+
+/// Play `station`, and return its name for the `--tui` interface to show.
+fn switch_to_favorite_station(station: Remote, runtime: &tokio::runtime::Handle) -> String {
+    let station_name = station.name.clone();
+    switch_station(station, runtime);
+    station_name
+}
+
+/// Play `station` without restarting, and remember it for `--resume`.
+fn switch_station(station: Remote, runtime: &tokio::runtime::Handle) {
+    log::info!("Switching to station {}: {}", station.name, station.url);
+    if let Some(player) = PLAYER.lock().unwrap().as_mut() {
+        player.play(&station.url, StreamFormat::from_name(&station.format));
+        media_controls::set_paused(false);
+        update_song_progress(SongProgress::resume_live);
+        update_progress_bar(|p| p.set_prefix(get_progress_bar_prefix(Some(&*player))));
     }
+    print_above_progress_bar(&format!(
+        "{}    {}",
+        "Station:".bright_green(),
+        station.name
+    ));
+
+    let station_id = station.id;
+    runtime.spawn(async move {
+        if let Err(e) =
+            persisted_state::update(|state| state.last_station_id = Some(station_id)).await
+        {
+            log::warn!("Failed to save selected station: {:#}", e);
+        }
+    });
 }
 
-/// When user press 0-9 on keyboard, adjust player volume.
-fn handle_keyboard_input() -> ! {
+/// Draw the `--tui` interface and handle keys in it, until quitting.
+///
+/// Like `handle_keyboard_input`, this runs on its own thread,
+/// and keys do the same as in `KEYBOARD_SHORTCUTS`, except that panes take the place of printed text.
+fn run_tui(
+    runtime: &tokio::runtime::Handle,
+    mut station_name: String,
+    listener_metric: ListenerMetric,
+) {
+    let mut tui = match tui::Tui::enter() {
+        Ok(tui) => tui,
+        Err(e) => {
+            tui::restore_terminal();
+            log::error!("Failed to start full-screen interface: {:#}", e);
+            terminal::print_error(format!("Failed to start full-screen interface: {:#}", e));
+            SHUTDOWN.notify_one();
+            return;
+        }
+    };
+
+    let mut pane = tui::Pane::SongHistory;
+    let mut selected_station = 0;
+
     loop {
-        if let Some(n) = terminal::read_char().ok().and_then(|c| c.to_digit(10)) {
-            if let Some(player) = PLAYER.lock().unwrap().as_mut() {
-                let volume = n as u8;
-                if player.volume() == volume {
-                    continue;
+        let stations = LATEST_MESSAGE
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(code_radio_api::get_stations_from_message)
+            .unwrap_or_default();
+        selected_station = selected_station.min(stations.len().saturating_sub(1));
+
+        let view = get_tui_view(
+            &station_name,
+            listener_metric,
+            pane,
+            &stations,
+            selected_station,
+        );
+        if let Err(e) = tui.draw(&view) {
+            log::warn!("Failed to draw full-screen interface: {:#}", e);
+        }
+
+        let key = match tui.read_key(TUI_REFRESH_INTERVAL) {
+            Ok(Some(key)) => key,
+            Ok(None) => continue,
+            Err(e) => {
+                log::warn!("Failed to read key: {:#}", e);
+                continue;
+            }
+        };
+
+        if pane == tui::Pane::Stations {
+            match key {
+                tui::Key::Up => selected_station = selected_station.saturating_sub(1),
+                tui::Key::Down => selected_station += 1,
+                tui::Key::Enter => {
+                    if let Some(station) = stations.get(selected_station) {
+                        station_name = station.name.clone();
+                        switch_station(station.clone(), runtime);
+                    }
+                    pane = tui::Pane::SongHistory;
+                }
+                tui::Key::Esc | tui::Key::Char('s' | 'S') => pane = tui::Pane::SongHistory,
+                tui::Key::Interrupt => break,
+                _ => {}
+            }
+            continue;
+        }
+
+        let c = match key {
+            tui::Key::Char(c) => c,
+            tui::Key::Alt(digit @ '1'..='9') => {
+                let number = digit.to_digit(10).unwrap_or_default() as usize;
+                if let Some(name) = switch_to_favorite(number, runtime) {
+                    station_name = name;
+                }
+                continue;
+            }
+            tui::Key::Esc | tui::Key::Interrupt => break,
+            _ => continue,
+        };
+        let shortcut = match KEYBOARD_SHORTCUTS.iter().find(|s| s.keys.contains(&c)) {
+            Some(shortcut) => shortcut,
+            None => continue,
+        };
+
+        match shortcut.action {
+            KeyAction::Volume => {
+                if let Some(n) = c.to_digit(10) {
+                    set_volume(n as u8);
+                }
+            }
+            KeyAction::Mute => toggle_mute(),
+            KeyAction::VolumePreset => switch_to_next_volume_preset(),
+            KeyAction::Pause => toggle_pause(),
+            KeyAction::SongHistory => pane = tui::Pane::SongHistory,
+            KeyAction::Lyrics => pane = tui::Pane::Lyrics,
+            KeyAction::CopySong => copy_current_song(),
+            KeyAction::SwitchStation => {
+                pane = tui::Pane::Stations;
+                selected_station = stations
+                    .iter()
+                    .position(|s| s.name == station_name)
+                    .unwrap_or_default();
+            }
+            KeyAction::NextFavorite => {
+                if let Some(name) = switch_to_next_favorite(runtime) {
+                    station_name = name;
                 }
-                player.set_volume(volume);
-                update_progress_bar(|p| p.set_prefix(get_progress_bar_prefix(Some(volume))));
             }
+            KeyAction::Favorite => {}
+            KeyAction::Reconnect => reconnect_stream(),
+            KeyAction::Help => pane = tui::Pane::Help,
+            KeyAction::Quit => break,
         }
     }
+
+    drop(tui); // Restore the terminal before quitting
+    SHUTDOWN.notify_one();
+}
+
+fn get_tui_view(
+    station_name: &str,
+    listener_metric: ListenerMetric,
+    pane: tui::Pane,
+    stations: &[Remote],
+    selected_station: usize,
+) -> tui::View {
+    let latest_message = LATEST_MESSAGE.lock().unwrap().clone();
+    let song = latest_message
+        .as_ref()
+        .map(|m| m.now_playing.song.clone())
+        .unwrap_or_default();
+    let total_seconds = latest_message
+        .as_ref()
+        .map_or(0, |m| m.now_playing.duration.max(0) as u64);
+    let total_seconds = (total_seconds > 0).then_some(total_seconds);
+    let elapsed_seconds = update_song_progress(|s| s.elapsed_seconds()).unwrap_or_default();
+
+    let pane_lines = match pane {
+        tui::Pane::SongHistory => latest_message
+            .iter()
+            .flat_map(|m| &m.song_history)
+            .map(|history| {
+                format!(
+                    "{} - {}",
+                    utils::sanitize_metadata_text(&history.song.artist),
+                    utils::sanitize_metadata_text(&history.song.title)
+                )
+            })
+            .collect(),
+        tui::Pane::Lyrics => match song.lyrics.trim() {
+            "" => vec!["No lyrics available.".to_owned()],
+            lyrics => lyrics.lines().map(utils::sanitize_metadata_text).collect(),
+        },
+        tui::Pane::Help => get_keyboard_shortcut_lines(),
+        tui::Pane::Stations => stations.iter().map(|s| s.name.clone()).collect(),
+    };
+
+    tui::View {
+        station: station_name.to_owned(),
+        title: utils::sanitize_metadata_text(&song.title),
+        artist: utils::sanitize_metadata_text(&song.artist),
+        album: utils::sanitize_metadata_text(&song.album),
+        volume: get_progress_bar_prefix(PLAYER.lock().unwrap().as_ref()),
+        listeners: latest_message.as_ref().map_or_else(String::new, |m| {
+            get_progress_bar_suffix(&m.listeners, listener_metric)
+        }),
+        progress_info: get_progress_bar_progress_info(elapsed_seconds, total_seconds),
+        progress_ratio: total_seconds.map_or(0.0, |total| elapsed_seconds as f64 / total as f64),
+        pane,
+        pane_lines,
+        selected_line: (pane == tui::Pane::Stations).then_some(selected_station),
+    }
 }
 
-async fn select_station_interactively() -> Result<Remote> {
+/// Returns `None` if user cancels the prompt with Esc or Ctrl+C.
+async fn select_station_interactively() -> Result<Option<Remote>> {
     let loading_spinner = ProgressBar::new_spinner()
         .with_style(ProgressStyle::with_template("{spinner} {msg}")?)
         .with_message("Connecting...");
@@ -299,18 +2517,44 @@ async fn select_station_interactively() -> Result<Remote> {
 
     loading_spinner.finish_and_clear();
 
-    let station_names: Vec<&str> = stations.iter().map(|s| s.name.as_str()).collect();
+    prompt_station(stations)
+}
 
-    let selected_station_name = Select::new("Select a station:", station_names)
-        .with_page_size(8)
-        .prompt()?;
-    let selected_station = stations
+/// Returns `None` if user cancels the prompt with Esc or Ctrl+C.
+///
+/// Type to filter stations by name, bitrate or format, like "96" or "aac".
+fn prompt_station(mut stations: Vec<Remote>) -> Result<Option<Remote>> {
+    let station_labels: Vec<String> = stations
         .iter()
-        .find(|s| s.name == selected_station_name)
-        .unwrap()
-        .clone();
+        .map(|s| format!("{} ({}kbps {})", s.name, s.bitrate, s.format))
+        .collect();
+
+    let selected_station = match Select::new("Select a station:", station_labels)
+        .with_page_size(8)
+        .with_filter(&|filter, _, label, _| fuzzy_match(filter, label))
+        .raw_prompt()
+    {
+        Ok(selected_station) => selected_station,
+        Err(InquireError::OperationCanceled | InquireError::OperationInterrupted) => {
+            return Ok(None)
+        }
+        Err(e) => return Err(e.into()),
+    };
+    // Map back by index, since labels of different stations may look alike
+    let selected_station = stations.swap_remove(selected_station.index);
 
     println!();
 
-    Ok(selected_station)
+    Ok(Some(selected_station))
+}
+
+/// Whether all characters in `filter` appear in `text` in order, ignoring case and spaces,
+/// like "lo96" matches "Low bitrate (96kbps mp3)".
+fn fuzzy_match(filter: &str, text: &str) -> bool {
+    let mut text_chars = text.chars().flat_map(char::to_lowercase);
+    filter
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .all(|filter_char| text_chars.any(|text_char| text_char == filter_char))
 }
@@ -0,0 +1,70 @@
+//! Integration with the operating system's media controls, like MPRIS on Linux,
+//! System Media Transport Controls on Windows and Now Playing on macOS,
+//! so media keys and desktop widgets can show the current song and control the player.
+//!
+//! Each platform is behind a cargo feature. Without any, all functions here do nothing.
+
+#[cfg(all(target_os = "linux", feature = "mpris"))]
+mod mpris;
+#[cfg(all(target_os = "macos", feature = "now-playing"))]
+mod now_playing;
+#[cfg(all(windows, feature = "smtc"))]
+mod smtc;
+
+use crate::models::code_radio::NowPlaying;
+
+/// A command sent from the operating system's media controls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    not(any(
+        all(target_os = "linux", feature = "mpris"),
+        all(windows, feature = "smtc"),
+        all(target_os = "macos", feature = "now-playing")
+    )),
+    allow(dead_code)
+)]
+pub enum MediaCommand {
+    Play,
+    Pause,
+    PlayPause,
+    Stop,
+}
+
+/// Run `app`, keeping the main thread for media controls which need it, like Now Playing on macOS.
+pub fn run_app(app: fn()) {
+    #[cfg(all(target_os = "macos", feature = "now-playing"))]
+    now_playing::run_app(app);
+    #[cfg(not(all(target_os = "macos", feature = "now-playing")))]
+    app();
+}
+
+/// Start the media controls in the background. `on_command` is called from a background thread.
+#[allow(unused_variables)]
+pub fn start(on_command: impl Fn(MediaCommand) + Send + Sync + 'static) {
+    #[cfg(all(target_os = "linux", feature = "mpris"))]
+    mpris::start(on_command);
+    #[cfg(all(windows, feature = "smtc"))]
+    smtc::start(on_command);
+    #[cfg(all(target_os = "macos", feature = "now-playing"))]
+    now_playing::start(on_command);
+}
+
+#[allow(unused_variables)]
+pub fn set_now_playing(now_playing: &NowPlaying) {
+    #[cfg(all(target_os = "linux", feature = "mpris"))]
+    mpris::set_now_playing(now_playing);
+    #[cfg(all(windows, feature = "smtc"))]
+    smtc::set_now_playing(now_playing);
+    #[cfg(all(target_os = "macos", feature = "now-playing"))]
+    now_playing::set_now_playing(now_playing);
+}
+
+#[allow(unused_variables)]
+pub fn set_paused(paused: bool) {
+    #[cfg(all(target_os = "linux", feature = "mpris"))]
+    mpris::set_paused(paused);
+    #[cfg(all(windows, feature = "smtc"))]
+    smtc::set_paused(paused);
+    #[cfg(all(target_os = "macos", feature = "now-playing"))]
+    now_playing::set_paused(paused);
+}
@@ -0,0 +1,135 @@
+use anyhow::Result;
+use mpris_server::{Metadata, PlaybackStatus, Player, Time};
+use once_cell::sync::OnceCell;
+use std::{sync::Arc, thread};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+use super::MediaCommand;
+use crate::{models::code_radio::NowPlaying, utils};
+
+static UPDATE_SENDER: OnceCell<UnboundedSender<Update>> = OnceCell::new();
+
+enum Update {
+    NowPlaying {
+        title: String,
+        artist: String,
+        album: String,
+        art_url: String,
+        duration_seconds: i64,
+    },
+    Paused(bool),
+}
+
+/// `mpris_server::Player` can't be moved between threads,
+/// so it lives on its own thread with a single-threaded runtime.
+pub fn start(on_command: impl Fn(MediaCommand) + Send + Sync + 'static) {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    if UPDATE_SENDER.set(sender).is_err() {
+        return; // Already started
+    }
+
+    thread::spawn(move || {
+        let runtime = match tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        {
+            Ok(runtime) => runtime,
+            Err(e) => {
+                log::warn!("Failed to start MPRIS runtime: {:#}", e);
+                return;
+            }
+        };
+
+        let local_set = tokio::task::LocalSet::new();
+        if let Err(e) = local_set.block_on(&runtime, run(Arc::new(on_command), receiver)) {
+            log::warn!("MPRIS is unavailable: {:#}", e);
+        }
+    });
+}
+
+pub fn set_now_playing(now_playing: &NowPlaying) {
+    let song = &now_playing.song;
+    send_update(Update::NowPlaying {
+        title: utils::sanitize_metadata_text(&song.title),
+        artist: utils::sanitize_metadata_text(&song.artist),
+        album: utils::sanitize_metadata_text(&song.album),
+        art_url: song.art.clone(),
+        duration_seconds: now_playing.duration,
+    });
+}
+
+pub fn set_paused(paused: bool) {
+    send_update(Update::Paused(paused));
+}
+
+fn send_update(update: Update) {
+    if let Some(sender) = UPDATE_SENDER.get() {
+        // Fails only if MPRIS is unavailable, which has already been logged
+        let _ = sender.send(update);
+    }
+}
+
+async fn run(
+    on_command: Arc<impl Fn(MediaCommand) + 'static>,
+    mut receiver: UnboundedReceiver<Update>,
+) -> Result<()> {
+    let player = Player::builder("CodeRadio")
+        .identity("Code Radio")
+        .can_control(true)
+        .can_play(true)
+        .can_pause(true)
+        .can_go_next(false)
+        .can_go_previous(false)
+        .can_seek(false)
+        .build()
+        .await?;
+
+    let on_play = on_command.clone();
+    player.connect_play(move |_| on_play(MediaCommand::Play));
+    let on_pause = on_command.clone();
+    player.connect_pause(move |_| on_pause(MediaCommand::Pause));
+    let on_play_pause = on_command.clone();
+    player.connect_play_pause(move |_| on_play_pause(MediaCommand::PlayPause));
+    player.connect_stop(move |_| on_command(MediaCommand::Stop));
+
+    tokio::task::spawn_local(player.run());
+    player.set_playback_status(PlaybackStatus::Playing).await?;
+
+    while let Some(update) = receiver.recv().await {
+        let result = match update {
+            Update::NowPlaying {
+                title,
+                artist,
+                album,
+                art_url,
+                duration_seconds,
+            } => {
+                let mut metadata = Metadata::builder()
+                    .title(title)
+                    .artist([artist])
+                    .album(album);
+                if !art_url.is_empty() {
+                    metadata = metadata.art_url(art_url);
+                }
+                if duration_seconds > 0 {
+                    metadata = metadata.length(Time::from_secs(duration_seconds));
+                }
+                player.set_metadata(metadata.build()).await
+            }
+            Update::Paused(paused) => {
+                let status = if paused {
+                    PlaybackStatus::Paused
+                } else {
+                    PlaybackStatus::Playing
+                };
+                player.set_playback_status(status).await
+            }
+        };
+
+        if let Err(e) = result {
+            log::warn!("Failed to update MPRIS: {:#}", e);
+        }
+    }
+
+    Ok(())
+}
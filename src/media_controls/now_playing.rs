@@ -0,0 +1,109 @@
+use block2::RcBlock;
+use objc2::{class, msg_send, msg_send_id, rc::Id, runtime::AnyObject};
+use objc2_foundation::{NSNumber, NSString};
+use std::{sync::Arc, thread};
+
+use super::MediaCommand;
+use crate::{models::code_radio::NowPlaying, utils};
+
+#[link(name = "MediaPlayer", kind = "framework")]
+extern "C" {
+    static MPMediaItemPropertyTitle: &'static NSString;
+    static MPMediaItemPropertyArtist: &'static NSString;
+    static MPMediaItemPropertyAlbumTitle: &'static NSString;
+    static MPNowPlayingInfoPropertyIsLiveStream: &'static NSString;
+}
+
+extern "C" {
+    fn dispatch_main() -> !;
+}
+
+// MPNowPlayingPlaybackState
+const PLAYBACK_STATE_PLAYING: usize = 1;
+const PLAYBACK_STATE_PAUSED: usize = 2;
+
+// MPRemoteCommandHandlerStatus
+const COMMAND_HANDLER_STATUS_SUCCESS: isize = 0;
+
+/// Remote command handlers are called on the main queue,
+/// so run `app` on another thread, and keep the main thread serving the main queue.
+/// Exits the process when `app` returns.
+pub fn run_app(app: fn()) -> ! {
+    thread::spawn(move || {
+        app();
+        std::process::exit(0);
+    });
+
+    unsafe { dispatch_main() }
+}
+
+pub fn start(on_command: impl Fn(MediaCommand) + Send + Sync + 'static) {
+    let on_command = Arc::new(on_command);
+
+    objc2::rc::autoreleasepool(|_| unsafe {
+        let command_center: *mut AnyObject =
+            msg_send![class!(MPRemoteCommandCenter), sharedCommandCenter];
+
+        let commands: [(*mut AnyObject, MediaCommand); 4] = [
+            (msg_send![command_center, playCommand], MediaCommand::Play),
+            (msg_send![command_center, pauseCommand], MediaCommand::Pause),
+            (
+                msg_send![command_center, togglePlayPauseCommand],
+                MediaCommand::PlayPause,
+            ),
+            (msg_send![command_center, stopCommand], MediaCommand::Stop),
+        ];
+
+        for (command, media_command) in commands {
+            let on_command = on_command.clone();
+            let handler = RcBlock::new(move |_event: *mut AnyObject| -> isize {
+                on_command(media_command);
+                COMMAND_HANDLER_STATUS_SUCCESS
+            });
+            // The command copies and keeps the handler
+            let _: *mut AnyObject = msg_send![command, addTargetWithHandler: &*handler];
+            let _: () = msg_send![command, setEnabled: true];
+        }
+    });
+
+    set_playback_state(PLAYBACK_STATE_PLAYING);
+}
+
+pub fn set_now_playing(now_playing: &NowPlaying) {
+    let song = &now_playing.song;
+    let title = NSString::from_str(&utils::sanitize_metadata_text(&song.title));
+    let artist = NSString::from_str(&utils::sanitize_metadata_text(&song.artist));
+    let album = NSString::from_str(&utils::sanitize_metadata_text(&song.album));
+    let is_live_stream = NSNumber::new_bool(true);
+
+    objc2::rc::autoreleasepool(|_| unsafe {
+        let info: Id<AnyObject> = msg_send_id![class!(NSMutableDictionary), new];
+        let entries: [(&NSString, &AnyObject); 4] = [
+            (MPMediaItemPropertyTitle, &title),
+            (MPMediaItemPropertyArtist, &artist),
+            (MPMediaItemPropertyAlbumTitle, &album),
+            (MPNowPlayingInfoPropertyIsLiveStream, &is_live_stream),
+        ];
+        for (key, value) in entries {
+            let _: () = msg_send![&info, setObject: value, forKey: key];
+        }
+
+        let info_center: *mut AnyObject = msg_send![class!(MPNowPlayingInfoCenter), defaultCenter];
+        let _: () = msg_send![info_center, setNowPlayingInfo: &*info];
+    });
+}
+
+pub fn set_paused(paused: bool) {
+    set_playback_state(if paused {
+        PLAYBACK_STATE_PAUSED
+    } else {
+        PLAYBACK_STATE_PLAYING
+    });
+}
+
+fn set_playback_state(playback_state: usize) {
+    objc2::rc::autoreleasepool(|_| unsafe {
+        let info_center: *mut AnyObject = msg_send![class!(MPNowPlayingInfoCenter), defaultCenter];
+        let _: () = msg_send![info_center, setPlaybackState: playback_state];
+    });
+}
@@ -0,0 +1,154 @@
+use anyhow::Result;
+use once_cell::sync::OnceCell;
+use std::{
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+};
+use windows::{
+    core::HSTRING,
+    Foundation::{TypedEventHandler, Uri},
+    Media::{
+        MediaPlaybackStatus, MediaPlaybackType, Playback::MediaPlayer,
+        SystemMediaTransportControls, SystemMediaTransportControlsButton,
+        SystemMediaTransportControlsButtonPressedEventArgs,
+    },
+    Storage::Streams::RandomAccessStreamReference,
+};
+
+use super::MediaCommand;
+use crate::{models::code_radio::NowPlaying, utils};
+
+static UPDATE_SENDER: OnceCell<Mutex<Sender<Update>>> = OnceCell::new();
+
+enum Update {
+    NowPlaying {
+        title: String,
+        artist: String,
+        album: String,
+        art_url: String,
+    },
+    Paused(bool),
+}
+
+/// System Media Transport Controls need a window, or a `MediaPlayer` which is never used to play anything.
+/// The latter also works in a console, so the controls are taken from an idle `MediaPlayer`,
+/// which lives on its own thread.
+pub fn start(on_command: impl Fn(MediaCommand) + Send + Sync + 'static) {
+    let (sender, receiver) = mpsc::channel();
+    if UPDATE_SENDER.set(Mutex::new(sender)).is_err() {
+        return; // Already started
+    }
+
+    thread::spawn(move || {
+        if let Err(e) = run(Arc::new(on_command), &receiver) {
+            log::warn!("System Media Transport Controls are unavailable: {:#}", e);
+        }
+    });
+}
+
+pub fn set_now_playing(now_playing: &NowPlaying) {
+    let song = &now_playing.song;
+    send_update(Update::NowPlaying {
+        title: utils::sanitize_metadata_text(&song.title),
+        artist: utils::sanitize_metadata_text(&song.artist),
+        album: utils::sanitize_metadata_text(&song.album),
+        art_url: song.art.clone(),
+    });
+}
+
+pub fn set_paused(paused: bool) {
+    send_update(Update::Paused(paused));
+}
+
+fn send_update(update: Update) {
+    if let Some(sender) = UPDATE_SENDER.get() {
+        // Fails only if the controls are unavailable, which has already been logged
+        let _ = sender.lock().unwrap().send(update);
+    }
+}
+
+fn run(
+    on_command: Arc<impl Fn(MediaCommand) + Send + Sync + 'static>,
+    receiver: &Receiver<Update>,
+) -> Result<()> {
+    // Keep the player alive, or the controls go away
+    let media_player = MediaPlayer::new()?;
+    media_player.CommandManager()?.SetIsEnabled(false)?;
+
+    let controls = media_player.SystemMediaTransportControls()?;
+    controls.SetIsEnabled(true)?;
+    controls.SetIsPlayEnabled(true)?;
+    controls.SetIsPauseEnabled(true)?;
+    controls.SetIsStopEnabled(true)?;
+    controls.SetIsNextEnabled(false)?;
+    controls.SetIsPreviousEnabled(false)?;
+
+    controls.ButtonPressed(&TypedEventHandler::new(
+        move |_: &Option<SystemMediaTransportControls>,
+              args: &Option<SystemMediaTransportControlsButtonPressedEventArgs>| {
+            if let Some(args) = args {
+                match args.Button()? {
+                    SystemMediaTransportControlsButton::Play => on_command(MediaCommand::Play),
+                    SystemMediaTransportControlsButton::Pause => on_command(MediaCommand::Pause),
+                    SystemMediaTransportControlsButton::Stop => on_command(MediaCommand::Stop),
+                    _ => {}
+                }
+            }
+            Ok(())
+        },
+    ))?;
+
+    controls.SetPlaybackStatus(MediaPlaybackStatus::Playing)?;
+
+    while let Ok(update) = receiver.recv() {
+        let result = match update {
+            Update::NowPlaying {
+                title,
+                artist,
+                album,
+                art_url,
+            } => set_metadata(&controls, &title, &artist, &album, &art_url),
+            Update::Paused(paused) => {
+                let status = if paused {
+                    MediaPlaybackStatus::Paused
+                } else {
+                    MediaPlaybackStatus::Playing
+                };
+                controls.SetPlaybackStatus(status).map_err(Into::into)
+            }
+        };
+
+        if let Err(e) = result {
+            log::warn!("Failed to update System Media Transport Controls: {:#}", e);
+        }
+    }
+
+    Ok(())
+}
+
+fn set_metadata(
+    controls: &SystemMediaTransportControls,
+    title: &str,
+    artist: &str,
+    album: &str,
+    art_url: &str,
+) -> Result<()> {
+    let updater = controls.DisplayUpdater()?;
+    updater.SetType(MediaPlaybackType::Music)?;
+
+    let music_properties = updater.MusicProperties()?;
+    music_properties.SetTitle(&HSTRING::from(title))?;
+    music_properties.SetArtist(&HSTRING::from(artist))?;
+    music_properties.SetAlbumTitle(&HSTRING::from(album))?;
+
+    if !art_url.is_empty() {
+        let art_uri = Uri::CreateUri(&HSTRING::from(art_url))?;
+        updater.SetThumbnail(&RandomAccessStreamReference::CreateFromUri(&art_uri)?)?;
+    }
+
+    updater.Update()?;
+    Ok(())
+}
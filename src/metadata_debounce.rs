@@ -0,0 +1,92 @@
+use crate::models::code_radio::CodeRadioMessage;
+use futures_util::{Stream, StreamExt};
+use std::time::Duration;
+
+/// `first`, followed by the messages of `message_stream` arriving within `window` after it.
+///
+/// With a zero `window`, returns `first` alone right away.
+pub async fn collect_burst(
+    first: CodeRadioMessage,
+    message_stream: &mut (impl Stream<Item = CodeRadioMessage> + Unpin),
+    window: Duration,
+) -> Vec<CodeRadioMessage> {
+    let mut messages = vec![first];
+    if window.is_zero() {
+        return messages;
+    }
+
+    let deadline = tokio::time::Instant::now() + window;
+    while let Ok(Some(message)) = tokio::time::timeout_at(deadline, message_stream.next()).await {
+        messages.push(message);
+    }
+    messages
+}
+
+/// Coalesce a burst of messages, keeping only the latest message of each song.
+///
+/// Messages of the same song are redundant except for the latest one,
+/// but a song change in the middle of the burst must not be dropped.
+///
+/// For example, `[A1, A2, B1, B2]` becomes `[A2, B2]`.
+pub fn coalesce(messages: Vec<CodeRadioMessage>) -> Vec<CodeRadioMessage> {
+    let mut coalesced: Vec<CodeRadioMessage> = Vec::with_capacity(messages.len());
+    for message in messages {
+        if let Some(last) = coalesced.last_mut() {
+            if last.now_playing.song.id == message.now_playing.song.id {
+                *last = message;
+                continue;
+            }
+        }
+        coalesced.push(message);
+    }
+    coalesced
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::stream;
+
+    fn message(song_id: &str, elapsed: i64) -> CodeRadioMessage {
+        let mut message = CodeRadioMessage::default();
+        message.now_playing.song.id = song_id.to_owned();
+        message.now_playing.elapsed = elapsed;
+        message
+    }
+
+    #[tokio::test]
+    async fn burst_within_window_yields_last_message() {
+        let mut message_stream =
+            stream::iter(vec![message("a", 2), message("a", 3)]).chain(stream::pending());
+
+        let messages = collect_burst(
+            message("a", 1),
+            &mut message_stream,
+            Duration::from_millis(50),
+        )
+        .await;
+        assert_eq!(messages.len(), 3);
+        assert_eq!(coalesce(messages), vec![message("a", 3)]);
+    }
+
+    #[tokio::test]
+    async fn zero_window_passes_every_message_through() {
+        let mut message_stream = stream::iter(vec![message("a", 2), message("a", 3)]);
+
+        let messages = collect_burst(message("a", 1), &mut message_stream, Duration::ZERO).await;
+        assert_eq!(coalesce(messages), vec![message("a", 1)]);
+        // The rest are left for the next round
+        assert_eq!(message_stream.next().await, Some(message("a", 2)));
+    }
+
+    #[test]
+    fn song_change_is_kept() {
+        let messages = vec![
+            message("a", 1),
+            message("a", 2),
+            message("b", 1),
+            message("b", 2),
+        ];
+        assert_eq!(coalesce(messages), vec![message("a", 2), message("b", 2)]);
+    }
+}
@@ -0,0 +1,55 @@
+use crate::models::code_radio::CodeRadioMessage;
+use anyhow::{Context, Result};
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::Path,
+};
+
+/// Appends each received `CodeRadioMessage` to a file as a line of JSON,
+/// so the session can be replayed later with `--simulate`.
+pub struct MetadataRecorder {
+    file: File,
+}
+
+impl MetadataRecorder {
+    pub fn create(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open \"{}\"", path.display()))?;
+        Ok(Self { file })
+    }
+
+    pub fn record(&mut self, message: &CodeRadioMessage) -> Result<()> {
+        let mut line = serde_json::to_string(message)?;
+        line.push('\n');
+        self.file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Read messages recorded by `MetadataRecorder`. Empty lines are skipped.
+pub fn read_messages(path: &Path) -> Result<Vec<CodeRadioMessage>> {
+    let file =
+        File::open(path).with_context(|| format!("Failed to open \"{}\"", path.display()))?;
+
+    let mut messages = Vec::new();
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let message = serde_json::from_str(&line)
+            .with_context(|| format!("Invalid message at line {}", i + 1))?;
+        messages.push(message);
+    }
+    Ok(messages)
+}
+
+/// The server's unix timestamp when the message was generated.
+/// Used to replay recorded messages with their original pacing.
+pub fn get_server_timestamp(message: &CodeRadioMessage) -> i64 {
+    message.now_playing.played_at + message.now_playing.elapsed
+}
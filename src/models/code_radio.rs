@@ -1,12 +1,17 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 
+// Every struct uses `#[serde(default)]`, so a field missing from the API response
+// falls back to its default value instead of failing to parse the whole message.
+
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct CodeRadioMessage {
     pub station: Station,
     pub listeners: Listeners,
     pub live: Live,
     pub now_playing: NowPlaying,
+    #[serde(deserialize_with = "null_as_default")]
     pub playing_next: PlayingNext,
     pub song_history: Vec<SongHistory>,
     pub is_online: bool,
@@ -14,6 +19,7 @@ pub struct CodeRadioMessage {
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Station {
     pub id: i64,
     pub name: String,
@@ -32,6 +38,7 @@ pub struct Station {
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Mount {
     pub path: String,
     pub is_default: bool,
@@ -44,6 +51,7 @@ pub struct Mount {
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Listeners {
     pub total: i64,
     pub unique: i64,
@@ -51,6 +59,7 @@ pub struct Listeners {
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Remote {
     pub id: i64,
     pub name: String,
@@ -61,58 +70,85 @@ pub struct Remote {
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Live {
     pub is_live: bool,
+    #[serde(deserialize_with = "null_as_default")]
     pub streamer_name: String,
     pub broadcast_start: Value,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct NowPlaying {
     pub elapsed: i64,
     pub remaining: i64,
     pub sh_id: i64,
     pub played_at: i64,
     pub duration: i64,
+    #[serde(deserialize_with = "null_as_default")]
     pub playlist: String,
+    #[serde(deserialize_with = "null_as_default")]
     pub streamer: String,
     pub is_request: bool,
     pub song: Song,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Song {
     pub id: String,
+    #[serde(deserialize_with = "null_as_default")]
     pub text: String,
+    #[serde(deserialize_with = "null_as_default")]
     pub artist: String,
+    #[serde(deserialize_with = "null_as_default")]
     pub title: String,
+    #[serde(deserialize_with = "null_as_default")]
     pub album: String,
+    #[serde(deserialize_with = "null_as_default")]
     pub genre: String,
+    #[serde(deserialize_with = "null_as_default")]
     pub lyrics: String,
+    #[serde(deserialize_with = "null_as_default")]
     pub art: String,
     pub custom_fields: Vec<Value>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct PlayingNext {
     pub cued_at: i64,
     pub duration: i64,
+    #[serde(deserialize_with = "null_as_default")]
     pub playlist: String,
     pub is_request: bool,
     pub song: Song,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SongHistory {
     pub sh_id: i64,
     pub played_at: i64,
     pub duration: i64,
+    #[serde(deserialize_with = "null_as_default")]
     pub playlist: String,
+    #[serde(deserialize_with = "null_as_default")]
     pub streamer: String,
     pub is_request: bool,
     pub song: Song,
 }
 
+/// Deserialize `null` as the default value, for fields the API may send as `null`.
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::deserialize(deserializer)?.unwrap_or_default())
+}
+
 impl From<Mount> for Remote {
     fn from(mount: Mount) -> Self {
         Self {
@@ -125,3 +161,35 @@ impl From<Mount> for Remote {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_partial_message() {
+        // Missing fields and `null`s fall back to defaults, instead of failing the whole message
+        let message: CodeRadioMessage = serde_json::from_str(
+            r#"{
+                "station": { "name": "Code Radio" },
+                "now_playing": {
+                    "duration": 200,
+                    "playlist": null,
+                    "song": { "id": "abc", "title": "Title", "artist": null }
+                },
+                "playing_next": null
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(message.station.name, "Code Radio");
+        assert!(message.station.mounts.is_empty());
+        assert_eq!(message.now_playing.duration, 200);
+        assert_eq!(message.now_playing.elapsed, 0);
+        assert_eq!(message.now_playing.playlist, "");
+        assert_eq!(message.now_playing.song.title, "Title");
+        assert_eq!(message.now_playing.song.artist, "");
+        assert_eq!(message.playing_next, Default::default());
+        assert!(message.song_history.is_empty());
+    }
+}
@@ -4,6 +4,7 @@ use super::code_radio::CodeRadioMessage;
 
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SeverSentEventsChannelMessage<TData> {
+    #[serde(default)]
     pub channel: String,
     pub r#pub: Pub<TData>,
 }
@@ -11,6 +12,7 @@ pub struct SeverSentEventsChannelMessage<TData> {
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Pub<TData> {
     pub data: TData,
+    #[serde(default)]
     pub offset: i64,
 }
 
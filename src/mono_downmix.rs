@@ -0,0 +1,123 @@
+use rodio::Source;
+use std::time::Duration;
+
+/// A `Source` wrapper which averages the channels of each frame, and plays the average on every channel,
+/// so nothing is lost on devices with only one working speaker.
+///
+/// Mono sources are left untouched.
+pub struct MonoDownmix<S>
+where
+    S: Source<Item = i16>,
+{
+    source: S,
+    average: i16,
+    /// How many more times to yield `average` for the current frame
+    pending_samples: u16,
+}
+
+impl<S> MonoDownmix<S>
+where
+    S: Source<Item = i16>,
+{
+    pub fn new(source: S) -> Self {
+        Self {
+            source,
+            average: 0,
+            pending_samples: 0,
+        }
+    }
+}
+
+impl<S> Source for MonoDownmix<S>
+where
+    S: Source<Item = i16>,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        let pending = usize::from(self.pending_samples);
+        self.source.current_frame_len().map(|len| len + pending)
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.source.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+impl<S> Iterator for MonoDownmix<S>
+where
+    S: Source<Item = i16>,
+{
+    type Item = i16;
+
+    #[inline]
+    fn next(&mut self) -> Option<i16> {
+        if self.pending_samples > 0 {
+            self.pending_samples -= 1;
+            return Some(self.average);
+        }
+
+        let first_sample = self.source.next()?;
+
+        // Query channels after `next()`, since the source may have just moved to a new frame
+        let channels = self.source.channels();
+        if channels <= 1 {
+            return Some(first_sample);
+        }
+
+        let mut sum = i32::from(first_sample);
+        let mut count = 1;
+        for _ in 1..channels {
+            match self.source.next() {
+                Some(sample) => {
+                    sum += i32::from(sample);
+                    count += 1;
+                }
+                None => break, // Truncated frame at the end of the stream
+            }
+        }
+
+        self.average = (sum / count) as i16;
+        self.pending_samples = count as u16 - 1;
+        Some(self.average)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::buffer::SamplesBuffer;
+
+    #[test]
+    fn average_stereo_frames() {
+        let source =
+            SamplesBuffer::new(2, 44100, vec![100_i16, 300, -200, 200, i16::MAX, i16::MAX]);
+        let downmix = MonoDownmix::new(source);
+
+        assert_eq!(downmix.channels(), 2);
+        assert_eq!(downmix.sample_rate(), 44100);
+        assert_eq!(
+            downmix.collect::<Vec<_>>(),
+            vec![200, 200, 0, 0, i16::MAX, i16::MAX]
+        );
+    }
+
+    #[test]
+    fn leave_mono_untouched() {
+        let source = SamplesBuffer::new(1, 44100, vec![100_i16, -100, 42]);
+        let downmix = MonoDownmix::new(source);
+
+        assert_eq!(downmix.channels(), 1);
+        assert_eq!(downmix.collect::<Vec<_>>(), vec![100, -100, 42]);
+    }
+}
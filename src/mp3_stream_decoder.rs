@@ -1,11 +1,10 @@
 #![allow(dead_code, unused_variables, unused_mut)]
 
 use minimp3::{Decoder, Frame};
-use std::io::Read;
+use rodio::Source;
+use std::io::{Chain, Cursor, Read};
 use std::time::Duration;
 
-use crate::Source;
-
 /// This is a modified version of [rodio's Mp3Decoder](https://github.com/RustAudio/rodio/blob/55d957f8b40c59fccea4162c4b03f6dd87a7a4d9/src/decoder/mp3.rs)
 /// which removes the "Seek" trait bound for streaming network audio.
 ///
@@ -15,7 +14,7 @@ pub struct Mp3StreamDecoder<R>
 where
     R: Read,
 {
-    decoder: Decoder<R>,
+    decoder: Decoder<Chain<Cursor<Vec<u8>>, R>>,
     current_frame: Frame,
     current_frame_offset: usize,
 }
@@ -24,12 +23,26 @@ impl<R> Mp3StreamDecoder<R>
 where
     R: Read,
 {
+    /// Returns `Err(data)` if `data` doesn't start with MP3 frames.
+    /// In that case the probed bytes have already been consumed from `data`.
     pub fn new(mut data: R) -> Result<Self, R> {
-        if !is_mp3(data.by_ref()) {
+        let mut probed_bytes = Vec::with_capacity(MP3_PROBE_SIZE);
+        if data
+            .by_ref()
+            .take(MP3_PROBE_SIZE as u64)
+            .read_to_end(&mut probed_bytes)
+            .is_err()
+            || !is_mp3(&probed_bytes)
+        {
             return Err(data);
         }
-        let mut decoder = Decoder::new(data);
-        let current_frame = decoder.next_frame().unwrap();
+
+        // Decode from the probed bytes first, so playback starts from the beginning of the stream
+        let mut decoder = Decoder::new(Cursor::new(probed_bytes).chain(data));
+        let current_frame = match decoder.next_frame() {
+            Ok(frame) => frame,
+            Err(_) => return Err(decoder.into_inner().into_inner().1),
+        };
 
         Ok(Self {
             decoder,
@@ -38,7 +51,7 @@ where
         })
     }
     pub fn into_inner(self) -> R {
-        self.decoder.into_inner()
+        self.decoder.into_inner().into_inner().1
     }
 }
 
@@ -48,7 +61,7 @@ where
 {
     #[inline]
     fn current_frame_len(&self) -> Option<usize> {
-        Some(self.current_frame.data.len())
+        Some(self.current_frame.data.len() - self.current_frame_offset)
     }
 
     #[inline]
@@ -90,18 +103,11 @@ where
     }
 }
 
-/// Always returns true.
-fn is_mp3<R>(mut data: R) -> bool
-where
-    R: Read,
-{
-    true
-
-    // Returns true if the stream contains mp3 data, then resets it to where it was.
-    // let stream_pos = data.seek(SeekFrom::Current(0)).unwrap();
-    // let mut decoder = Decoder::new(data.by_ref());
-    // let ok = decoder.next_frame().is_ok();
-    // data.seek(SeekFrom::Start(stream_pos)).unwrap();
+/// How many bytes to read from the start of a stream to check if it's MP3.
+/// Enough for several frames even at 320 kbps.
+const MP3_PROBE_SIZE: usize = 16 * 1024;
 
-    // ok
+/// Returns true if `data` starts with decodable MP3 frames.
+fn is_mp3(data: &[u8]) -> bool {
+    Decoder::new(data).next_frame().is_ok()
 }
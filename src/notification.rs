@@ -0,0 +1,27 @@
+use notify_rust::Notification;
+
+use crate::{models::code_radio::Song, utils};
+
+/// Show a desktop notification for a new song.
+///
+/// Showing a notification may block, for example while talking to the notification daemon over D-Bus,
+/// so it is done on a blocking thread. Errors, like no notification daemon running, are only logged.
+pub fn show_song_notification(song: &Song) {
+    let title = utils::sanitize_metadata_text(&song.title);
+    let body = format!(
+        "{}\n{}",
+        utils::sanitize_metadata_text(&song.artist),
+        utils::sanitize_metadata_text(&song.album)
+    );
+
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = Notification::new()
+            .appname("Code Radio")
+            .summary(&title)
+            .body(&body)
+            .show()
+        {
+            log::warn!("Failed to show notification: {:#}", e);
+        }
+    });
+}
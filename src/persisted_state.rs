@@ -0,0 +1,94 @@
+//! State remembered across restarts, like the last used volume, the last selected station and favorite stations.
+
+use anyhow::{anyhow, Result};
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, time::Duration};
+use tokio::sync::{watch, Mutex};
+
+use crate::app_dirs;
+
+const STATE_FILE_NAME: &str = "state.json";
+
+/// Wait for volume to stop changing for this long before saving it,
+/// so quickly pressing several keys only writes the file once.
+const SAVE_VOLUME_DEBOUNCE: Duration = Duration::from_secs(1);
+
+static STATE_FILE_PATH: Lazy<Option<PathBuf>> = Lazy::new(|| {
+    app_dirs::get_project_dirs().map(|dirs| dirs.data_local_dir().join(STATE_FILE_NAME))
+});
+
+static FILE_IO_MUTEX: Mutex<()> = Mutex::const_new(());
+
+static VOLUME_SENDER: OnceCell<watch::Sender<u8>> = OnceCell::new();
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PersistedState {
+    pub volume: Option<u8>,
+    /// ID of the station last selected with `--select-station`
+    pub last_station_id: Option<i64>,
+    /// IDs of stations added with `--favorite`, in the order they were added
+    pub favorite_station_ids: Vec<i64>,
+}
+
+/// Returns the default state if the state file doesn't exist or can't be read.
+pub async fn load() -> PersistedState {
+    let _file_io_mutex_guard = FILE_IO_MUTEX.lock().await;
+    read_state_file().await.unwrap_or_default()
+}
+
+/// Read, modify and write the state file.
+pub async fn update(modify: impl FnOnce(&mut PersistedState)) -> Result<()> {
+    let _file_io_mutex_guard = FILE_IO_MUTEX.lock().await;
+
+    let mut state = read_state_file().await.unwrap_or_default();
+    modify(&mut state);
+
+    let state_file_path = STATE_FILE_PATH
+        .as_ref()
+        .ok_or_else(|| anyhow!("No home directory found"))?;
+    if let Some(parent) = state_file_path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(state_file_path, serde_json::to_string_pretty(&state)?).await?;
+
+    Ok(())
+}
+
+async fn read_state_file() -> Option<PersistedState> {
+    let state_file_content = tokio::fs::read_to_string(STATE_FILE_PATH.as_ref()?)
+        .await
+        .ok()?;
+    serde_json::from_str(&state_file_content).ok()
+}
+
+/// Start saving volume passed to `save_volume` in the background. Must be called from async code.
+pub fn start_saving_volume(initial_volume: u8) {
+    let (sender, mut receiver) = watch::channel(initial_volume);
+    if VOLUME_SENDER.set(sender).is_err() {
+        return; // Already started
+    }
+
+    tokio::spawn(async move {
+        while receiver.changed().await.is_ok() {
+            tokio::time::sleep(SAVE_VOLUME_DEBOUNCE).await;
+            let volume = *receiver.borrow_and_update();
+            if let Err(e) = update(|state| state.volume = Some(volume)).await {
+                log::warn!("Failed to save volume: {:#}", e);
+            }
+        }
+    });
+}
+
+/// Save volume in the background, debounced. Can be called from any thread.
+/// Does nothing if `start_saving_volume` hasn't been called.
+pub fn save_volume(volume: u8) {
+    if let Some(sender) = VOLUME_SENDER.get() {
+        sender.send_if_modified(|current_volume| {
+            let modified = *current_volume != volume;
+            *current_volume = volume;
+            modified
+        });
+    }
+}
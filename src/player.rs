@@ -1,79 +1,643 @@
-use anyhow::{Context, Result};
-use rodio::{OutputStream, Sink};
+use anyhow::{anyhow, Result};
+use reqwest::header::CONTENT_TYPE;
 use std::{
-    sync::mpsc::{self, Sender},
+    io::Read,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicI8, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc,
+    },
     thread,
+    time::{Duration, Instant},
 };
+use tokio::sync::watch;
 
-use crate::mp3_stream_decoder::Mp3StreamDecoder;
+use crate::{
+    audio_backend::{self, AudioBackend, AudioBackendKind, AudioSource},
+    http_client,
+    icy_metadata::{self, IcyMetadataReader},
+    mono_downmix::MonoDownmix,
+    read_ahead_reader::ReadAheadReader,
+    sample_activity::{SampleActivity, SampleActivitySource},
+    stereo_balance::StereoBalance,
+    stream_decoder,
+    tee_reader::{RecordingTarget, TeeReader},
+    utils,
+};
 
 /// A player for streaming network audio.
 pub struct Player {
     sender: Sender<PlayerMessage>,
-    volume: u8, // Between 0 and 9
+    volume: u8,             // Between 0 and 9
+    balance: Arc<AtomicI8>, // Between -9 (left) and 9 (right)
+    paused: bool,
+    muted: bool,
+    volume_before_mute: u8,
+    /// URL and format of the stream last passed to `play`
+    current_stream: Option<(String, Option<StreamFormat>)>,
+    state_sender: Arc<watch::Sender<PlayerState>>,
+    state_receiver: watch::Receiver<PlayerState>,
+    icy_title_receiver: watch::Receiver<Option<String>>,
+    connection_info_receiver: watch::Receiver<Option<ConnectionInfo>>,
+}
+
+/// How the player is connected to the current stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionInfo {
+    /// `None` if the HTTP client doesn't know, like behind some proxies
+    pub remote_addr: Option<SocketAddr>,
+    /// Like "HTTPS"
+    pub protocol: String,
+    pub http_version: reqwest::Version,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlayerState {
+    /// `play` has not been called yet.
+    Idle,
+    /// Connecting to the stream and decoding the first audio frame.
+    Connecting,
+    /// Audio is confirmed flowing to the output device.
+    Playing,
+    /// Playing, but the stream stalled, like on a slow connection, so no audio is coming out.
+    /// Back to `Playing` once audio resumes.
+    Buffering,
+    /// The stream dropped while playing. Reconnecting to it.
+    Reconnecting,
+    /// Failed to start the stream. Contains the error message.
+    /// Waits for the next `play`, like when switching stations.
+    Failed(String),
+    /// The stream dropped, and reconnecting to it ran out of attempts. Contains the error message.
+    GaveUp(String),
+}
+
+/// Options that can't be changed after creating a `Player`.
+pub struct PlayerOptions {
+    pub audio_backend: AudioBackendKind,
+    /// Name of the output device of the rodio backend. `None` for the default device.
+    pub device: Option<String>,
+    /// How many times in a row to try reconnecting when the stream drops. 0 to never reconnect.
+    pub reconnect_attempts: u32,
+    /// Append the raw bytes of every stream played to this file.
+    pub record_path: Option<PathBuf>,
+    /// Instead of `record_path`, record each song to its own file in this directory,
+    /// like `01 - Artist - Title.mp3`. Call `Player::split_recording` when the song changes.
+    pub record_split_dir: Option<PathBuf>,
+    /// Fade in new streams, and fade out the current one when switching, over this long. Zero to disable.
+    pub fade: Duration,
+    /// Multiply the amplitude of every volume step by this, so volume 9 can be louder than the stream itself.
+    /// 1 for no gain. Capped to `MAX_GAIN`. Gain above 1 may clip.
+    pub gain: f32,
+    /// Read ahead up to this many bytes of the stream, so short network stalls don't cause dropouts.
+    /// Zero to disable.
+    pub buffer_size: usize,
+    /// Downmix stereo to mono, played on both channels, for setups with only one working speaker.
+    pub mono: bool,
+}
+
+/// The highest `PlayerOptions::gain`. Anything louder clips on most streams.
+pub const MAX_GAIN: f32 = 3.0;
+
+/// Audio format of a stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamFormat {
+    Mp3,
+    Aac,
+    Ogg,
+}
+
+impl StreamFormat {
+    /// Parse the `format` field of a station's mount or remote, like "mp3" or "aac".
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "mp3" => Some(Self::Mp3),
+            "aac" | "aacp" => Some(Self::Aac),
+            "ogg" => Some(Self::Ogg),
+            _ => None,
+        }
+    }
+
+    /// Like "mp3", for naming recordings.
+    pub fn file_extension(self) -> &'static str {
+        match self {
+            Self::Mp3 => "mp3",
+            Self::Aac => "aac",
+            Self::Ogg => "ogg",
+        }
+    }
+
+    fn from_content_type(content_type: &str) -> Option<Self> {
+        match content_type.split(';').next()?.trim() {
+            "audio/mpeg" | "audio/mp3" => Some(Self::Mp3),
+            "audio/aac" | "audio/aacp" => Some(Self::Aac),
+            "audio/ogg" | "application/ogg" => Some(Self::Ogg),
+            _ => None,
+        }
+    }
 }
 
 enum PlayerMessage {
-    Play { listen_url: String, volume: u8 },
-    Volume { volume: u8 },
+    Play {
+        listen_url: String,
+        format: Option<StreamFormat>,
+        volume: u8,
+    },
+    Volume {
+        volume: u8,
+    },
+    Pause,
+    Resume,
+    SplitRecording {
+        artist: String,
+        title: String,
+    },
 }
 
 impl Player {
     /// Creating a `Player` might be time consuming. It might take several seconds on first run.
-    pub fn try_new() -> Result<Self> {
-        OutputStream::try_default().context("Audio device initialization failed")?;
-
+    pub fn try_new(options: PlayerOptions) -> Result<Self> {
         let (sender, receiver) = mpsc::channel();
+        let (init_sender, init_receiver) = mpsc::sync_channel(1);
+        let balance = Arc::new(AtomicI8::new(0));
+        let (state_sender, state_receiver) = watch::channel(PlayerState::Idle);
+        let state_sender = Arc::new(state_sender);
+        let (icy_title_sender, icy_title_receiver) = watch::channel(None);
+        let icy_title_sender = Arc::new(icy_title_sender);
+        let (connection_info_sender, connection_info_receiver) = watch::channel(None);
+
+        let thread_balance = balance.clone();
+        let thread_state_sender = state_sender.clone();
         thread::spawn(move || {
-            let (_stream, stream_handle) = OutputStream::try_default().unwrap();
+            // Some backends can't be moved between threads, so create it on this thread
+            let backend = audio_backend::create(options.audio_backend, options.device.as_deref());
+            let backend = match backend {
+                Ok(backend) => {
+                    init_sender.send(Ok(())).unwrap();
+                    backend
+                }
+                Err(e) => {
+                    init_sender.send(Err(e)).unwrap();
+                    return;
+                }
+            };
 
-            let (mut current_listen_url, mut current_volume) = loop {
-                if let Ok(PlayerMessage::Play { listen_url, volume }) = receiver.recv() {
-                    break (listen_url, volume);
+            Self::run(
+                backend,
+                &receiver,
+                &thread_balance,
+                &thread_state_sender,
+                &icy_title_sender,
+                &connection_info_sender,
+                &options,
+            );
+        });
+
+        init_receiver
+            .recv()
+            .map_err(|_| anyhow!("Audio backend thread exited unexpectedly"))??;
+
+        Ok(Self {
+            sender,
+            volume: 9,
+            balance,
+            paused: false,
+            muted: false,
+            volume_before_mute: 9,
+            current_stream: None,
+            state_sender,
+            state_receiver,
+            icy_title_receiver,
+            connection_info_receiver,
+        })
+    }
+
+    /// Play streams on the player thread until `Player` is dropped.
+    ///
+    /// If a stream fails to start, report `PlayerState::Failed` right away, so users see why.
+    /// If a stream drops while playing, like on network errors, reconnect to it with exponential backoff,
+    /// and report `PlayerState::GaveUp` after `reconnect_attempts` failed attempts in a row,
+    /// or once `http_client::NetworkOptions::max_retries` runs out, whichever comes first.
+    fn run(
+        mut backend: Box<dyn AudioBackend>,
+        receiver: &Receiver<PlayerMessage>,
+        balance: &Arc<AtomicI8>,
+        state_sender: &watch::Sender<PlayerState>,
+        icy_title_sender: &Arc<watch::Sender<Option<String>>>,
+        connection_info_sender: &watch::Sender<Option<ConnectionInfo>>,
+        options: &PlayerOptions,
+    ) {
+        // How often to check whether the stream has dropped or stalled
+        const STREAM_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+        // How long without audio until the stream counts as stalled
+        const STALL_THRESHOLD: Duration = Duration::from_secs(1);
+
+        let reconnect_attempts = options.reconnect_attempts;
+        let fade = options.fade;
+        let gain = options.gain.clamp(0.0, MAX_GAIN);
+        let buffer_size = options.buffer_size;
+        let recording = RecordingTarget::new(options.record_path.clone());
+        let mut recorded_songs = 0;
+
+        let (mut listen_url, mut format, mut volume) = loop {
+            match receiver.recv() {
+                Ok(PlayerMessage::Play {
+                    listen_url,
+                    format,
+                    volume,
+                }) => break (listen_url, format, volume),
+                Ok(_) => {}
+                Err(_) => return, // `Player` dropped
+            }
+        };
+        let mut paused = false;
+        let mut reconnect_attempt = 0; // 0 if not reconnecting
+
+        loop {
+            backend.stop();
+            // Start silent, and fade in once the stream starts
+            backend.set_volume(0.0);
+
+            let activity = Arc::new(SampleActivity::new());
+            let refilling = Arc::new(AtomicBool::new(false));
+            let mut is_buffering = false;
+            let play_result = Self::start_stream(
+                &listen_url,
+                format,
+                balance.clone(),
+                activity.clone(),
+                (buffer_size > 0).then(|| (buffer_size, refilling.clone())),
+                options.mono,
+                recording.clone(),
+                icy_title_sender.clone(),
+            )
+            .and_then(|(source, connection_info)| {
+                backend.play(source)?;
+                connection_info_sender.send_replace(Some(connection_info));
+                Ok(())
+            });
+            let mut is_playing = match play_result {
+                Ok(()) => {
+                    if paused {
+                        backend.pause();
+                    }
+                    if reconnect_attempt > 0 {
+                        log::info!("Reconnected to {}", listen_url);
+                    }
+                    reconnect_attempt = 0;
+                    http_client::reset_retries();
+                    state_sender.send_replace(PlayerState::Playing);
+
+                    let target_volume = Self::map_volume_to_rodio_volume(volume, gain);
+                    if paused {
+                        backend.set_volume(target_volume);
+                    } else {
+                        Self::fade(&mut *backend, 0.0, target_volume, fade);
+                    }
+                    true
+                }
+                Err(e) if reconnect_attempt == 0 => {
+                    log::error!("Failed to play {}: {:#}", listen_url, e);
+                    state_sender.send_replace(PlayerState::Failed(format!("{:#}", e)));
+                    false
+                }
+                Err(e) => {
+                    log::warn!(
+                        "Reconnect attempt {}/{} failed: {:#}",
+                        reconnect_attempt,
+                        reconnect_attempts,
+                        e
+                    );
+                    if reconnect_attempt >= reconnect_attempts {
+                        log::error!("Gave up reconnecting to {}", listen_url);
+                        state_sender.send_replace(PlayerState::GaveUp(format!(
+                            "Gave up reconnecting after {} attempts: {:#}",
+                            reconnect_attempts, e
+                        )));
+                        reconnect_attempt = 0;
+                    }
+                    false
                 }
             };
 
+            let mut next_reconnect_at = (reconnect_attempt > 0)
+                .then(|| Instant::now() + Self::get_reconnect_delay(reconnect_attempt + 1));
+
             loop {
-                let response = reqwest::blocking::get(&current_listen_url).unwrap();
-                let source = Mp3StreamDecoder::new(response).unwrap();
-                let sink = Sink::try_new(&stream_handle).unwrap();
-                sink.append(source);
-                sink.set_volume(Self::map_volume_to_rodio_volume(current_volume));
-
-                while let Ok(message) = receiver.recv() {
-                    match message {
-                        PlayerMessage::Play { listen_url, volume } => {
-                            current_listen_url = listen_url;
-                            current_volume = volume;
+                let timeout = next_reconnect_at.map_or(STREAM_CHECK_INTERVAL, |t| {
+                    t.saturating_duration_since(Instant::now())
+                });
+
+                match receiver.recv_timeout(timeout) {
+                    Ok(PlayerMessage::Play {
+                        listen_url: new_listen_url,
+                        format: new_format,
+                        volume: new_volume,
+                    }) => {
+                        if is_playing && !paused {
+                            let current_volume = Self::map_volume_to_rodio_volume(volume, gain);
+                            Self::fade(&mut *backend, current_volume, 0.0, fade);
+                        }
+                        listen_url = new_listen_url;
+                        format = new_format;
+                        volume = new_volume;
+                        paused = false;
+                        reconnect_attempt = 0;
+                        break;
+                    }
+                    Ok(PlayerMessage::Volume { volume: new_volume }) => {
+                        volume = new_volume;
+                        backend.set_volume(Self::map_volume_to_rodio_volume(volume, gain));
+                    }
+                    Ok(PlayerMessage::Pause) => {
+                        paused = true;
+                        backend.pause();
+                    }
+                    Ok(PlayerMessage::Resume) => {
+                        paused = false;
+                        activity.touch();
+                        backend.resume();
+                    }
+                    Ok(PlayerMessage::SplitRecording { artist, title }) => {
+                        if let Some(record_split_dir) = &options.record_split_dir {
+                            recorded_songs += 1;
+                            let path = Self::get_split_recording_path(
+                                record_split_dir,
+                                recorded_songs,
+                                &artist,
+                                &title,
+                                format.unwrap_or(StreamFormat::Mp3),
+                            );
+                            log::info!("Recording to {}", path.display());
+                            recording.switch_to(path);
+                        }
+                    }
+                    Err(RecvTimeoutError::Disconnected) => return, // `Player` dropped
+                    Err(RecvTimeoutError::Timeout) => {
+                        if next_reconnect_at.is_some_and(|t| Instant::now() >= t) {
+                            if !http_client::try_retry() {
+                                log::error!("Gave up reconnecting to {}", listen_url);
+                                let error = http_client::out_of_retries_error();
+                                state_sender.send_replace(PlayerState::GaveUp(error.to_string()));
+                                next_reconnect_at = None;
+                                reconnect_attempt = 0;
+                                continue;
+                            }
+                            reconnect_attempt += 1;
                             break;
                         }
-                        PlayerMessage::Volume { volume } => {
-                            current_volume = volume;
-                            sink.set_volume(Self::map_volume_to_rodio_volume(current_volume));
+
+                        if is_playing && !paused {
+                            let is_stalled = activity.idle_time() >= STALL_THRESHOLD
+                                || refilling.load(Ordering::Relaxed);
+                            if is_stalled != is_buffering {
+                                is_buffering = is_stalled;
+                                if is_stalled {
+                                    log::warn!("Stream {} stalled, buffering...", listen_url);
+                                    state_sender.send_replace(PlayerState::Buffering);
+                                } else {
+                                    log::info!("Stream {} resumed", listen_url);
+                                    state_sender.send_replace(PlayerState::Playing);
+                                }
+                            }
+                        }
+
+                        if is_playing && backend.is_finished() {
+                            is_playing = false;
+                            if reconnect_attempts == 0 {
+                                log::error!("Stream {} dropped", listen_url);
+                                state_sender
+                                    .send_replace(PlayerState::GaveUp("Stream dropped".to_owned()));
+                            } else {
+                                log::warn!("Stream {} dropped, reconnecting...", listen_url);
+                                state_sender.send_replace(PlayerState::Reconnecting);
+                                next_reconnect_at =
+                                    Some(Instant::now() + Self::get_reconnect_delay(1));
+                            }
                         }
                     }
                 }
             }
-        });
+        }
+    }
+
+    /// Ramp `backend`'s volume from `from` to `to` over `duration`, blocking the player thread meanwhile.
+    fn fade(backend: &mut dyn AudioBackend, from: f32, to: f32, duration: Duration) {
+        const FADE_STEPS: u32 = 20;
 
-        Ok(Self { sender, volume: 9 })
+        backend.set_volume(from);
+        if duration.is_zero() {
+            backend.set_volume(to);
+            return;
+        }
+
+        for step in 1..=FADE_STEPS {
+            thread::sleep(duration / FADE_STEPS);
+            backend.set_volume(from + (to - from) * step as f32 / FADE_STEPS as f32);
+        }
     }
 
-    pub fn play(&self, listen_url: &str) {
+    /// A new file in `dir` for recording a song, like `01 - Artist - Title.mp3`.
+    /// If the file already exists, a counter is appended, like `01 - Artist - Title (2).mp3`.
+    fn get_split_recording_path(
+        dir: &Path,
+        number: u32,
+        artist: &str,
+        title: &str,
+        format: StreamFormat,
+    ) -> PathBuf {
+        let name = if artist.is_empty() {
+            format!("{number:02} - {title}")
+        } else {
+            format!("{number:02} - {artist} - {title}")
+        };
+        let name = utils::sanitize_file_name(&name);
+        let extension = format.file_extension();
+
+        let mut path = dir.join(format!("{name}.{extension}"));
+        let mut counter = 1;
+        while path.exists() {
+            counter += 1;
+            path = dir.join(format!("{name} ({counter}).{extension}"));
+        }
+        path
+    }
+
+    /// Wait 1s before the first reconnect attempt, then double it for each attempt, up to 30s.
+    fn get_reconnect_delay(attempt: u32) -> Duration {
+        const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+        let exponent = attempt.saturating_sub(1).min(5);
+        Duration::from_secs(1 << exponent).min(MAX_RECONNECT_DELAY)
+    }
+
+    /// Connect to `listen_url`, check its content type and decode the first audio frame.
+    /// Also returns how it's connected, for showing it to users.
+    ///
+    /// If `format` is `None`, detect it from the content type, or assume MP3 if that's unknown.
+    ///
+    /// With `read_ahead`, read ahead up to that many bytes, and set the flag while the buffer is refilling.
+    #[allow(clippy::too_many_arguments)]
+    fn start_stream(
+        listen_url: &str,
+        format: Option<StreamFormat>,
+        balance: Arc<AtomicI8>,
+        activity: Arc<SampleActivity>,
+        read_ahead: Option<(usize, Arc<AtomicBool>)>,
+        mono: bool,
+        recording: RecordingTarget,
+        icy_title_sender: Arc<watch::Sender<Option<String>>>,
+    ) -> Result<(AudioSource, ConnectionInfo)> {
+        log::debug!("Connecting to stream {}", listen_url);
+        let response = http_client::blocking_client()
+            .get(listen_url)
+            .header(icy_metadata::REQUEST_HEADER, "1")
+            .send()
+            .map_err(http_client::explain_timeout)?
+            .error_for_status()?;
+
+        let connection_info = ConnectionInfo {
+            remote_addr: response.remote_addr(),
+            protocol: response.url().scheme().to_uppercase(),
+            http_version: response.version(),
+        };
+
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_owned);
+
+        if let Some(content_type) = &content_type {
+            if !content_type.starts_with("audio/")
+                && !content_type.starts_with("application/octet-stream")
+                && !content_type.starts_with("application/ogg")
+            {
+                return Err(anyhow!("Unexpected content type \"{}\"", content_type));
+            }
+        }
+
+        let format = format
+            .or_else(|| {
+                content_type
+                    .as_deref()
+                    .and_then(StreamFormat::from_content_type)
+            })
+            .unwrap_or(StreamFormat::Mp3);
+
+        let icy_metadata_interval = response
+            .headers()
+            .get(icy_metadata::METADATA_INTERVAL_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<usize>().ok())
+            .filter(|&interval| interval > 0);
+        log::debug!(
+            "Stream format: {:?}, content type: {:?}, ICY metadata interval: {:?}",
+            format,
+            content_type,
+            icy_metadata_interval
+        );
+        // Buffer the raw bytes, so ICY titles still come when the audio around them is decoded
+        let response: Box<dyn Read + Send> = match read_ahead {
+            Some((buffer_size, refilling)) => {
+                Box::new(ReadAheadReader::new(response, buffer_size, refilling))
+            }
+            None => Box::new(response),
+        };
+        let response: Box<dyn Read + Send> = match icy_metadata_interval {
+            Some(interval) => {
+                Box::new(IcyMetadataReader::new(response, interval, icy_title_sender))
+            }
+            None => response,
+        };
+
+        // Dropping the decoder closes the recording, like when switching stations or quitting
+        let response = TeeReader::new(response, recording);
+
+        let mut source = stream_decoder::get_decoder(format).decode(Box::new(response))?;
+        if mono {
+            source = Box::new(MonoDownmix::new(source));
+        }
+        // Centered streams are played untouched, without upmixing mono to stereo
+        if balance.load(Ordering::Relaxed) != 0 {
+            source = Box::new(StereoBalance::new(source, balance));
+        }
+        Ok((
+            Box::new(SampleActivitySource::new(source, activity)),
+            connection_info,
+        ))
+    }
+
+    /// Start playing a new stream. This also resumes the player if it is paused.
+    ///
+    /// If `format` is `None`, it's detected from the stream's content type.
+    pub fn play(&mut self, listen_url: &str, format: Option<StreamFormat>) {
+        self.paused = false;
+        self.current_stream = Some((listen_url.to_owned(), format));
+        self.state_sender.send_replace(PlayerState::Connecting);
+
         self.sender
             .send(PlayerMessage::Play {
                 listen_url: listen_url.to_owned(),
+                format,
                 volume: self.volume,
             })
             .unwrap();
     }
 
+    /// Record the song which just started to its own file, if `PlayerOptions::record_split_dir` is set.
+    pub fn split_recording(&self, artist: &str, title: &str) {
+        self.sender
+            .send(PlayerMessage::SplitRecording {
+                artist: artist.to_owned(),
+                title: title.to_owned(),
+            })
+            .unwrap();
+    }
+
+    /// Tear down the current stream and connect to it again, like when it gets stuck.
+    /// This also resumes the player if it is paused. Returns `false` if `play` has not been called yet.
+    pub fn reconnect(&mut self) -> bool {
+        match self.current_stream.clone() {
+            Some((listen_url, format)) => {
+                self.play(&listen_url, format);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// URL of the stream last passed to `play`.
+    pub fn current_stream_url(&self) -> Option<&str> {
+        self.current_stream.as_ref().map(|(url, _)| url.as_str())
+    }
+
+    /// Subscribe to state changes of the player.
+    pub fn state(&self) -> watch::Receiver<PlayerState> {
+        self.state_receiver.clone()
+    }
+
+    /// How the player is connected to the stream it last started playing.
+    /// `None` until a stream starts playing.
+    pub fn connection_info(&self) -> Option<ConnectionInfo> {
+        self.connection_info_receiver.borrow().clone()
+    }
+
+    /// Subscribe to the song title in the stream's ICY metadata, like "Artist - Title".
+    /// `None` until a stream which supports ICY metadata sends one.
+    pub fn icy_title(&self) -> watch::Receiver<Option<String>> {
+        self.icy_title_receiver.clone()
+    }
+
     pub const fn volume(&self) -> u8 {
         self.volume
     }
 
+    /// Setting volume also unmutes the player.
     pub fn set_volume(&mut self, volume: u8) {
         self.volume = Self::cap_volume(volume);
+        self.muted = false;
 
         self.sender
             .send(PlayerMessage::Volume {
@@ -82,13 +646,108 @@ impl Player {
             .unwrap();
     }
 
+    pub const fn is_muted(&self) -> bool {
+        self.muted
+    }
+
+    /// Mute or unmute the player. Unmuting restores the volume before muting.
+    /// Returns whether the player is muted after toggling.
+    pub fn toggle_mute(&mut self) -> bool {
+        if self.muted {
+            self.set_volume(self.volume_before_mute);
+        } else {
+            self.volume_before_mute = self.volume;
+            self.set_volume(0);
+            self.muted = true;
+        }
+
+        self.muted
+    }
+
+    pub const fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Pause or resume playback. Returns whether the player is paused after toggling.
+    pub fn toggle_pause(&mut self) -> bool {
+        self.paused = !self.paused;
+
+        let message = if self.paused {
+            PlayerMessage::Pause
+        } else {
+            PlayerMessage::Resume
+        };
+        self.sender.send(message).unwrap();
+
+        self.paused
+    }
+
+    /// Takes effect immediately on the currently playing stream, unless it was started centered,
+    /// in which case it takes effect from the next stream.
+    pub fn set_balance(&mut self, balance: i8) {
+        self.balance.store(balance.clamp(-9, 9), Ordering::Relaxed);
+    }
+
     /// Cap volume to a value between 0 and 9
     fn cap_volume(volume: u8) -> u8 {
         volume.min(9)
     }
 
-    /// Map a volume between 0 and 9 to between 0 and 1
-    fn map_volume_to_rodio_volume(volume: u8) -> f32 {
-        volume as f32 / 9_f32
+    /// Map a volume between 0 and 9 to an amplitude between 0 and `gain`.
+    ///
+    /// Loudness is perceived logarithmically, so each step changes the amplitude by the same number of decibels,
+    /// from -40 dB at 1 to 0 dB at 9, before applying `gain`. 0 is silence.
+    fn map_volume_to_rodio_volume(volume: u8, gain: f32) -> f32 {
+        const MIN_VOLUME_DECIBELS: f32 = -40.0;
+
+        if volume == 0 {
+            return 0.0;
+        }
+
+        let volume = Self::cap_volume(volume);
+        let decibels = MIN_VOLUME_DECIBELS * (9 - volume) as f32 / 8.0;
+        10_f32.powf(decibels / 20.0) * gain
+    }
+}
+
+/// Wait until the player confirms audio is flowing, or fails to start the stream.
+pub async fn wait_until_playing(mut state: watch::Receiver<PlayerState>) -> Result<()> {
+    loop {
+        let current_state = state.borrow().clone();
+        match current_state {
+            PlayerState::Playing | PlayerState::Buffering => return Ok(()),
+            PlayerState::Failed(e) | PlayerState::GaveUp(e) => return Err(anyhow!(e)),
+            PlayerState::Idle | PlayerState::Connecting | PlayerState::Reconnecting => {}
+        }
+        state.changed().await?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn volume_zero_is_silence() {
+        assert_eq!(Player::map_volume_to_rodio_volume(0, 1.0), 0.0);
+        assert_eq!(Player::map_volume_to_rodio_volume(0, MAX_GAIN), 0.0);
+    }
+
+    #[test]
+    fn volume_nine_is_unity() {
+        assert_eq!(Player::map_volume_to_rodio_volume(9, 1.0), 1.0);
+        assert_eq!(Player::map_volume_to_rodio_volume(9, 2.0), 2.0);
+        // Capped to 9
+        assert_eq!(Player::map_volume_to_rodio_volume(u8::MAX, 1.0), 1.0);
+    }
+
+    #[test]
+    fn volume_rises_monotonically() {
+        let volumes: Vec<f32> = (0..=9)
+            .map(|volume| Player::map_volume_to_rodio_volume(volume, 1.0))
+            .collect();
+        assert!(volumes.windows(2).all(|pair| pair[0] < pair[1]));
+        // -40 dB
+        assert!((volumes[1] - 0.01).abs() < 1e-6);
     }
 }
@@ -0,0 +1,139 @@
+use std::{
+    collections::VecDeque,
+    io::{self, Read},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    thread,
+};
+
+/// How many bytes the background thread reads at a time.
+const READ_CHUNK_SIZE: usize = 8 * 1024;
+
+/// A reader which reads ahead from `inner` on a background thread into a bounded buffer,
+/// so short network stalls drain the buffer instead of starving the decoder.
+///
+/// At the start, and whenever the buffer runs dry, reads wait until it's filled to half its capacity,
+/// or the stream ends, to build up a cushion for the next stall.
+/// `refilling` is set meanwhile, for showing a buffering indicator.
+///
+/// The background thread stops after its current read once this reader is dropped.
+pub struct ReadAheadReader {
+    shared: Arc<Shared>,
+    refilling: Arc<AtomicBool>,
+}
+
+struct Shared {
+    state: Mutex<BufferState>,
+    /// Notified when bytes are added to or taken from the buffer, or either side stops.
+    changed: Condvar,
+    capacity: usize,
+}
+
+#[derive(Default)]
+struct BufferState {
+    buffer: VecDeque<u8>,
+    /// `inner` reached its end. The bytes left in `buffer` can still be read.
+    finished: bool,
+    /// `inner` failed. Returned once `buffer` is drained.
+    error: Option<io::Error>,
+    /// The `ReadAheadReader` is dropped, so the background thread should stop.
+    closed: bool,
+    refilling: bool,
+}
+
+impl ReadAheadReader {
+    /// Read ahead at most `capacity` bytes, or 1 byte if it's 0.
+    pub fn new<R>(inner: R, capacity: usize, refilling: Arc<AtomicBool>) -> Self
+    where
+        R: Read + Send + 'static,
+    {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(BufferState::default()),
+            changed: Condvar::new(),
+            capacity: capacity.max(1),
+        });
+
+        let thread_shared = shared.clone();
+        thread::spawn(move || fill_buffer(inner, &thread_shared));
+
+        Self { shared, refilling }
+    }
+}
+
+impl Read for ReadAheadReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let refill_target = (self.shared.capacity / 2).max(1);
+        let mut state = self.shared.state.lock().unwrap();
+
+        if state.buffer.is_empty() && !state.finished && state.error.is_none() {
+            state.refilling = true;
+        }
+        while state.refilling
+            && state.buffer.len() < refill_target
+            && !state.finished
+            && state.error.is_none()
+        {
+            self.refilling.store(true, Ordering::Relaxed);
+            state = self.shared.changed.wait(state).unwrap();
+        }
+        state.refilling = false;
+        self.refilling.store(false, Ordering::Relaxed);
+
+        if state.buffer.is_empty() {
+            return match state.error.take() {
+                Some(e) => Err(e),
+                None => Ok(0), // `inner` finished
+            };
+        }
+
+        let len = buf.len().min(state.buffer.len());
+        for (byte, buffered_byte) in buf.iter_mut().zip(state.buffer.drain(..len)) {
+            *byte = buffered_byte;
+        }
+        self.shared.changed.notify_all();
+
+        Ok(len)
+    }
+}
+
+impl Drop for ReadAheadReader {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().closed = true;
+        self.shared.changed.notify_all();
+    }
+}
+
+/// Read from `inner` into the shared buffer until it ends, fails, or the reader is dropped.
+fn fill_buffer(mut inner: impl Read, shared: &Shared) {
+    let mut chunk = vec![0; READ_CHUNK_SIZE.min(shared.capacity)];
+
+    loop {
+        let read_result = inner.read(&mut chunk);
+
+        let mut state = shared.state.lock().unwrap();
+        match read_result {
+            Ok(0) => state.finished = true,
+            Ok(len) => {
+                while state.buffer.len() + len > shared.capacity && !state.closed {
+                    state = shared.changed.wait(state).unwrap();
+                }
+                state.buffer.extend(&chunk[..len]);
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => state.error = Some(e),
+        }
+        let should_stop = state.finished || state.error.is_some() || state.closed;
+        drop(state);
+        shared.changed.notify_all();
+
+        if should_stop {
+            return;
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use rodio::Source;
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// Record the time only once every this many samples, since getting the time for every sample is wasteful.
+const SAMPLES_PER_RECORD: u32 = 1024;
+
+/// When a source last produced a sample, shared between the audio thread and the player thread.
+pub struct SampleActivity {
+    created_at: Instant,
+    last_active_millis: AtomicU64, // Since `created_at`
+}
+
+impl SampleActivity {
+    pub fn new() -> Self {
+        Self {
+            created_at: Instant::now(),
+            last_active_millis: AtomicU64::new(0),
+        }
+    }
+
+    /// Count as active now, like when resuming, so the time spent paused isn't taken as a stall.
+    pub fn touch(&self) {
+        let elapsed_millis = self.created_at.elapsed().as_millis() as u64;
+        self.last_active_millis
+            .store(elapsed_millis, Ordering::Relaxed);
+    }
+
+    /// How long since the source last produced a sample.
+    pub fn idle_time(&self) -> Duration {
+        let last_active_at = self.created_at
+            + Duration::from_millis(self.last_active_millis.load(Ordering::Relaxed));
+        last_active_at.elapsed()
+    }
+}
+
+/// A `Source` wrapper which records when samples are produced into a `SampleActivity`.
+///
+/// Decoders block while waiting for network data, so if no samples are produced for a while,
+/// the stream is stalled, like on a slow connection.
+pub struct SampleActivitySource<S>
+where
+    S: Source<Item = i16>,
+{
+    source: S,
+    activity: Arc<SampleActivity>,
+    samples_until_record: u32,
+}
+
+impl<S> SampleActivitySource<S>
+where
+    S: Source<Item = i16>,
+{
+    pub fn new(source: S, activity: Arc<SampleActivity>) -> Self {
+        activity.touch();
+        Self {
+            source,
+            activity,
+            samples_until_record: SAMPLES_PER_RECORD,
+        }
+    }
+}
+
+impl<S> Source for SampleActivitySource<S>
+where
+    S: Source<Item = i16>,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        self.source.current_frame_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.source.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+impl<S> Iterator for SampleActivitySource<S>
+where
+    S: Source<Item = i16>,
+{
+    type Item = i16;
+
+    #[inline]
+    fn next(&mut self) -> Option<i16> {
+        let sample = self.source.next()?;
+
+        self.samples_until_record -= 1;
+        if self.samples_until_record == 0 {
+            self.samples_until_record = SAMPLES_PER_RECORD;
+            self.activity.touch();
+        }
+
+        Some(sample)
+    }
+}
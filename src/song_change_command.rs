@@ -0,0 +1,38 @@
+use std::process::Stdio;
+use tokio::process::Command;
+
+use crate::{models::code_radio::Song, utils};
+
+/// Run a user's shell command for a new song, without waiting for it to finish.
+///
+/// Song info is passed in `CR_TITLE`, `CR_ARTIST` and `CR_ALBUM` environment variables.
+/// The command's output is discarded, so it can't mess up the progress bar.
+pub fn run(command: &str, song: &Song) {
+    let mut shell_command = create_shell_command(command);
+    shell_command
+        .env("CR_TITLE", utils::sanitize_metadata_text(&song.title))
+        .env("CR_ARTIST", utils::sanitize_metadata_text(&song.artist))
+        .env("CR_ALBUM", utils::sanitize_metadata_text(&song.album))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+
+    // tokio reaps the child process in background after it exits
+    if let Err(e) = shell_command.spawn() {
+        log::error!("Failed to run song change command \"{}\": {}", command, e);
+    }
+}
+
+#[cfg(windows)]
+fn create_shell_command(command: &str) -> Command {
+    let mut shell_command = Command::new("cmd");
+    shell_command.arg("/C").arg(command);
+    shell_command
+}
+
+#[cfg(not(windows))]
+fn create_shell_command(command: &str) -> Command {
+    let mut shell_command = Command::new("sh");
+    shell_command.arg("-c").arg(command);
+    shell_command
+}
@@ -0,0 +1,310 @@
+use crate::{
+    clock::{Clock, SystemClock},
+    models::code_radio::NowPlaying,
+};
+use std::time::{Duration, Instant};
+
+/// Elapsed seconds of the current song as heard, extrapolated from the last value synced from Code Radio's API.
+///
+/// The API reports where the live stream is. After pausing and resuming where it left off ("timeshift"),
+/// the audio is behind the live stream by the time spent paused, so the progress is kept behind by as much,
+/// until resuming with `resume_live`, like after reconnecting to the stream.
+///
+/// Never exceeds the song's duration if it's known, even if the next song's message is late.
+pub struct SongProgress<C: Clock = SystemClock> {
+    clock: C,
+    synced_elapsed_seconds: u64,
+    synced_at: Instant,
+    duration_seconds: u64, // 0 if unknown
+    /// How far the audio is behind the live stream
+    delay: Duration,
+    /// When it was paused, and the elapsed seconds then
+    paused: Option<(Instant, u64)>,
+}
+
+impl<C: Clock> SongProgress<C> {
+    pub fn new(clock: C, now_playing: &NowPlaying) -> Self {
+        let synced_elapsed_seconds = get_elapsed_seconds(now_playing, &clock);
+        let synced_at = clock.now();
+
+        Self {
+            clock,
+            synced_elapsed_seconds,
+            synced_at,
+            duration_seconds: now_playing.duration.max(0) as u64,
+            delay: Duration::ZERO,
+            paused: None,
+        }
+    }
+
+    /// Start tracking the next song, which is as far behind the live stream as this one,
+    /// and paused if this one is.
+    pub fn next_song(&self, now_playing: &NowPlaying) -> Self
+    where
+        C: Clone,
+    {
+        let mut song_progress = Self::new(self.clock.clone(), now_playing);
+        song_progress.delay = self.delay;
+        if let Some((paused_at, _)) = self.paused {
+            // As heard if it had been playing since `paused_at`, which is where resuming continues from
+            let delay = self.delay + self.clock.now().saturating_duration_since(paused_at);
+            let elapsed_seconds = song_progress
+                .live_elapsed_seconds()
+                .saturating_sub(delay.as_secs());
+            song_progress.paused = Some((paused_at, elapsed_seconds));
+        }
+        song_progress
+    }
+
+    /// Call this method when receiving a new message of the same song.
+    ///
+    /// While paused, the progress stays where the audio stopped.
+    pub fn sync(&mut self, now_playing: &NowPlaying) {
+        self.synced_elapsed_seconds = get_elapsed_seconds(now_playing, &self.clock);
+        self.synced_at = self.clock.now();
+        self.duration_seconds = now_playing.duration.max(0) as u64;
+    }
+
+    /// Stop counting elapsed seconds.
+    pub fn pause(&mut self) {
+        if self.paused.is_none() {
+            self.paused = Some((self.clock.now(), self.elapsed_seconds()));
+        }
+    }
+
+    /// Continue counting elapsed seconds from where it was paused, falling behind the live stream
+    /// by the time spent paused, like the audio does.
+    pub fn resume(&mut self) {
+        if let Some((paused_at, _)) = self.paused.take() {
+            self.delay += self.clock.now().saturating_duration_since(paused_at);
+        }
+    }
+
+    /// Jump to where the live stream is, like the audio does after reconnecting to it.
+    pub fn resume_live(&mut self) {
+        self.paused = None;
+        self.delay = Duration::ZERO;
+    }
+
+    pub fn elapsed_seconds(&self) -> u64 {
+        match self.paused {
+            Some((_, elapsed_seconds)) => elapsed_seconds,
+            None => self
+                .live_elapsed_seconds()
+                .saturating_sub(self.delay.as_secs()),
+        }
+    }
+
+    /// Elapsed seconds of the song in the live stream.
+    fn live_elapsed_seconds(&self) -> u64 {
+        let seconds_since_synced = self.clock.now().duration_since(self.synced_at).as_secs();
+        let elapsed_seconds = self.synced_elapsed_seconds + seconds_since_synced;
+        if self.duration_seconds > 0 {
+            elapsed_seconds.min(self.duration_seconds)
+        } else {
+            elapsed_seconds
+        }
+    }
+}
+
+/// Prefer deriving elapsed seconds from `played_at`,
+/// because the REST API's response is a cached static file and its `elapsed` may be stale.
+///
+/// Falls back to `elapsed` if `played_at` is missing or doesn't make sense (e.g. local clock is off),
+/// or to `duration - remaining` if `elapsed` is missing too. Never exceeds `duration` if it's known.
+pub fn get_elapsed_seconds(now_playing: &NowPlaying, clock: &impl Clock) -> u64 {
+    let duration = now_playing.duration;
+
+    if now_playing.played_at > 0 {
+        let elapsed_seconds = clock.unix_timestamp() - now_playing.played_at;
+        if elapsed_seconds >= 0 && (duration <= 0 || elapsed_seconds <= duration) {
+            return elapsed_seconds as u64;
+        }
+    }
+
+    let elapsed_seconds = if now_playing.elapsed <= 0 && now_playing.remaining > 0 {
+        duration - now_playing.remaining
+    } else {
+        now_playing.elapsed
+    };
+    if duration > 0 {
+        elapsed_seconds.clamp(0, duration) as u64
+    } else {
+        elapsed_seconds.max(0) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use std::time::Duration;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn now_playing(elapsed: i64, played_at: i64, duration: i64) -> NowPlaying {
+        NowPlaying {
+            elapsed,
+            played_at,
+            duration,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn progress_follows_clock() {
+        let clock = ManualClock::new(NOW);
+        let song_progress = SongProgress::new(&clock, &now_playing(10, 0, 200));
+        assert_eq!(song_progress.elapsed_seconds(), 10);
+
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(song_progress.elapsed_seconds(), 15);
+
+        clock.advance(Duration::from_secs(500));
+        assert_eq!(song_progress.elapsed_seconds(), 200);
+    }
+
+    #[test]
+    fn played_at_corrects_stale_elapsed() {
+        let clock = ManualClock::new(NOW);
+        // The cached REST API response says 10s, but the song started 40s ago
+        let mut song_progress = SongProgress::new(&clock, &now_playing(10, NOW - 40, 200));
+        assert_eq!(song_progress.elapsed_seconds(), 40);
+
+        clock.advance(Duration::from_secs(20));
+        song_progress.sync(&now_playing(15, NOW - 40, 200));
+        assert_eq!(song_progress.elapsed_seconds(), 60);
+    }
+
+    #[test]
+    fn pause_stops_progress() {
+        let clock = ManualClock::new(NOW);
+        let mut song_progress = SongProgress::new(&clock, &now_playing(10, 0, 200));
+
+        song_progress.pause();
+        clock.advance(Duration::from_secs(30));
+        song_progress.sync(&now_playing(40, 0, 200));
+        assert_eq!(song_progress.elapsed_seconds(), 10);
+
+        song_progress.resume();
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(song_progress.elapsed_seconds(), 15);
+    }
+
+    #[test]
+    fn timeshift_resume_continues_where_paused() {
+        let clock = ManualClock::new(NOW);
+        let mut song_progress = SongProgress::new(&clock, &now_playing(10, NOW - 10, 200));
+
+        song_progress.pause();
+        clock.advance(Duration::from_secs(60));
+        song_progress.sync(&now_playing(70, NOW - 10, 200));
+        song_progress.resume();
+        assert_eq!(song_progress.elapsed_seconds(), 10);
+
+        // Still behind the live stream after the next sync
+        clock.advance(Duration::from_secs(15));
+        song_progress.sync(&now_playing(85, NOW - 10, 200));
+        assert_eq!(song_progress.elapsed_seconds(), 25);
+    }
+
+    #[test]
+    fn live_resume_jumps_to_live_stream() {
+        let clock = ManualClock::new(NOW);
+        let mut song_progress = SongProgress::new(&clock, &now_playing(10, NOW - 10, 200));
+
+        song_progress.pause();
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(song_progress.elapsed_seconds(), 10);
+        song_progress.resume_live();
+        assert_eq!(song_progress.elapsed_seconds(), 70);
+    }
+
+    #[test]
+    fn live_resume_drops_timeshift() {
+        let clock = ManualClock::new(NOW);
+        let mut song_progress = SongProgress::new(&clock, &now_playing(10, 0, 200));
+
+        song_progress.pause();
+        clock.advance(Duration::from_secs(30));
+        song_progress.resume();
+        assert_eq!(song_progress.elapsed_seconds(), 10);
+
+        song_progress.resume_live();
+        assert_eq!(song_progress.elapsed_seconds(), 40);
+    }
+
+    #[test]
+    fn next_song_keeps_timeshift() {
+        let clock = ManualClock::new(NOW);
+        let mut song_progress = SongProgress::new(&clock, &now_playing(190, 0, 200));
+        song_progress.pause();
+        clock.advance(Duration::from_secs(20));
+        song_progress.resume();
+
+        // The next song started live, but it is heard 20s later
+        clock.advance(Duration::from_secs(25));
+        let next = song_progress.next_song(&now_playing(15, 0, 180));
+        assert_eq!(next.elapsed_seconds(), 0);
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(next.elapsed_seconds(), 5);
+    }
+
+    #[test]
+    fn next_song_while_paused_stays_paused() {
+        let clock = ManualClock::new(NOW);
+        let mut song_progress = SongProgress::new(&clock, &now_playing(10, 0, 200));
+        song_progress.pause();
+
+        clock.advance(Duration::from_secs(5));
+        let mut next = song_progress.next_song(&now_playing(30, 0, 180));
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(next.elapsed_seconds(), 25);
+
+        next.resume_live();
+        assert_eq!(next.elapsed_seconds(), 90);
+    }
+
+    #[test]
+    fn elapsed_seconds_prefers_played_at() {
+        let clock = ManualClock::new(NOW);
+        assert_eq!(
+            get_elapsed_seconds(&now_playing(10, NOW - 25, 200), &clock),
+            25
+        );
+    }
+
+    #[test]
+    fn elapsed_seconds_ignores_played_at_when_local_clock_is_off() {
+        let clock = ManualClock::new(NOW);
+        // `played_at` in the future, or longer ago than the song lasts
+        assert_eq!(
+            get_elapsed_seconds(&now_playing(10, NOW + 60, 200), &clock),
+            10
+        );
+        assert_eq!(
+            get_elapsed_seconds(&now_playing(10, NOW - 600, 200), &clock),
+            10
+        );
+    }
+
+    #[test]
+    fn elapsed_seconds_falls_back_to_remaining() {
+        let clock = ManualClock::new(NOW);
+        let now_playing = NowPlaying {
+            remaining: 150,
+            duration: 200,
+            ..Default::default()
+        };
+        assert_eq!(get_elapsed_seconds(&now_playing, &clock), 50);
+    }
+
+    #[test]
+    fn elapsed_seconds_clamped_to_duration() {
+        let clock = ManualClock::new(NOW);
+        assert_eq!(get_elapsed_seconds(&now_playing(300, 0, 200), &clock), 200);
+        assert_eq!(get_elapsed_seconds(&now_playing(-5, 0, 200), &clock), 0);
+        // Unknown duration
+        assert_eq!(get_elapsed_seconds(&now_playing(300, 0, 0), &clock), 300);
+    }
+}
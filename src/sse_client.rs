@@ -0,0 +1,196 @@
+//! A minimal Server-Sent Events client on top of `reqwest`,
+//! so the stream shares the network settings of other requests, like proxy.
+//!
+//! Spec: https://html.spec.whatwg.org/multipage/server-sent-events.html
+
+use anyhow::Result;
+use futures_util::Stream;
+use once_cell::sync::OnceCell;
+use reqwest::{header::ACCEPT, Url};
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+use tokio::sync::mpsc::{self, UnboundedSender};
+
+use crate::http_client;
+
+pub const DEFAULT_RECONNECT_INITIAL_DELAY: Duration = Duration::from_secs(1);
+pub const DEFAULT_RECONNECT_MAX_DELAY: Duration = Duration::from_secs(20);
+
+/// Initial and max delay of reconnecting
+static RECONNECT_DELAYS: OnceCell<(Duration, Duration)> = OnceCell::new();
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Event {
+    pub event_type: String,
+    pub data: String,
+}
+
+/// Connect to `url` in the background and stream its events.
+///
+/// If the connection drops, an error is yielded, then it reconnects after a delay,
+/// which starts at 1s and doubles on each failed attempt, up to 20s, unless set by `set_reconnect_delays`.
+/// The stream ends if it fails to connect on the first attempt,
+/// or runs out of reconnection attempts set by `http_client::NetworkOptions::max_retries`.
+pub fn connect(url: Url) -> impl Stream<Item = Result<Event>> {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let (initial_delay, max_delay) = *RECONNECT_DELAYS
+        .get_or_init(|| (DEFAULT_RECONNECT_INITIAL_DELAY, DEFAULT_RECONNECT_MAX_DELAY));
+
+    tokio::spawn(async move {
+        let mut reconnect_delay = initial_delay;
+        let mut reconnect_attempt = 0;
+        let mut has_connected = false;
+
+        loop {
+            let mut connected = false;
+            let result = read_events(&url, &sender, &mut connected).await;
+            if connected {
+                has_connected = true;
+                reconnect_delay = initial_delay;
+                reconnect_attempt = 0;
+                http_client::reset_retries();
+            }
+            if sender.is_closed() {
+                return; // Stream dropped
+            }
+
+            match result {
+                Ok(()) => log::warn!("Server-Sent Events connection closed by server"),
+                Err(e) => {
+                    log::warn!("Server-Sent Events error: {:#}", e);
+                    let _ = sender.send(Err(e));
+                }
+            }
+            if !has_connected {
+                return;
+            }
+            if !http_client::try_retry() {
+                log::error!("Gave up reconnecting to Server-Sent Events stream");
+                let _ = sender.send(Err(http_client::out_of_retries_error()));
+                return;
+            }
+
+            let delay = add_jitter(reconnect_delay);
+            reconnect_attempt += 1;
+            log::info!(
+                "Reconnecting to Server-Sent Events stream in {:.1}s (attempt {})",
+                delay.as_secs_f64(),
+                reconnect_attempt
+            );
+            tokio::time::sleep(delay).await;
+            reconnect_delay = (reconnect_delay * 2).min(max_delay);
+        }
+    });
+
+    futures_util::stream::unfold(receiver, |mut receiver| async move {
+        let item = receiver.recv().await?;
+        Some((item, receiver))
+    })
+}
+
+/// Set the delays of reconnecting for all streams connected afterwards. Can only be set once.
+pub fn set_reconnect_delays(initial_delay: Duration, max_delay: Duration) {
+    let _ = RECONNECT_DELAYS.set((initial_delay, max_delay.max(initial_delay)));
+}
+
+/// Randomize `delay` to between half and all of it,
+/// so clients disconnected by the same server outage don't all reconnect at the same moment.
+fn add_jitter(delay: Duration) -> Duration {
+    // Each `RandomState` is randomly seeded, which is random enough for jitter
+    let random = RandomState::new().build_hasher().finish();
+    let fraction = (random % 1000) as f64 / 1000.0;
+    delay.mul_f64(0.5 + fraction * 0.5)
+}
+
+/// Read events until the connection closes. Returns `Ok` if the server closed it normally.
+async fn read_events(
+    url: &Url,
+    sender: &UnboundedSender<Result<Event>>,
+    connected: &mut bool,
+) -> Result<()> {
+    log::debug!("Connecting to Server-Sent Events stream {}", url);
+    let mut response = http_client::client()
+        .get(url.clone())
+        .header(ACCEPT, "text/event-stream")
+        .send()
+        .await?
+        .error_for_status()?;
+    *connected = true;
+    log::debug!("Connected to Server-Sent Events stream");
+
+    let mut parser = EventParser::default();
+    while let Some(chunk) = response.chunk().await? {
+        for event in parser.feed(&chunk) {
+            if sender.send(Ok(event)).is_err() {
+                return Ok(()); // Stream dropped
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse the event stream format incrementally, from arbitrarily split chunks.
+#[derive(Default)]
+struct EventParser {
+    line_buffer: Vec<u8>,
+    event_type: String,
+    data: Option<String>,
+}
+
+impl EventParser {
+    fn feed(&mut self, chunk: &[u8]) -> Vec<Event> {
+        let mut events = Vec::new();
+
+        for &byte in chunk {
+            if byte != b'\n' {
+                self.line_buffer.push(byte);
+                continue;
+            }
+
+            let line_bytes = std::mem::take(&mut self.line_buffer);
+            let line = String::from_utf8_lossy(&line_bytes);
+            let line = line.strip_suffix('\r').unwrap_or(&line);
+            if let Some(event) = self.process_line(line) {
+                events.push(event);
+            }
+        }
+
+        events
+    }
+
+    /// Returns an event if `line` is the blank line ending one.
+    fn process_line(&mut self, line: &str) -> Option<Event> {
+        if line.is_empty() {
+            let data = self.data.take()?;
+            return Some(Event {
+                event_type: std::mem::take(&mut self.event_type),
+                data,
+            });
+        }
+
+        if line.starts_with(':') {
+            return None; // Comment, like keep-alive pings
+        }
+
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+
+        match field {
+            "event" => self.event_type = value.to_owned(),
+            "data" => match &mut self.data {
+                Some(data) => {
+                    data.push('\n');
+                    data.push_str(value);
+                }
+                None => self.data = Some(value.to_owned()),
+            },
+            _ => {} // "id" and "retry" are not needed
+        }
+
+        None
+    }
+}
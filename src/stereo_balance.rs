@@ -0,0 +1,161 @@
+use rodio::Source;
+use std::{
+    sync::{
+        atomic::{AtomicI8, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// A `Source` wrapper which pans audio to left or right by adjusting per-channel gain.
+///
+/// Balance is between -9 (left only) and 9 (right only). 0 means centered, which leaves samples untouched.
+///
+/// Mono sources are upmixed to stereo so they can be panned too.
+pub struct StereoBalance<S>
+where
+    S: Source<Item = i16>,
+{
+    source: S,
+    balance: Arc<AtomicI8>,
+    channel_index: u16,
+    last_channels: u16,
+    pending_right_sample: Option<i16>, // Right channel of an upmixed mono sample
+}
+
+impl<S> StereoBalance<S>
+where
+    S: Source<Item = i16>,
+{
+    pub fn new(source: S, balance: Arc<AtomicI8>) -> Self {
+        let last_channels = source.channels();
+        Self {
+            source,
+            balance,
+            channel_index: 0,
+            last_channels,
+            pending_right_sample: None,
+        }
+    }
+}
+
+impl<S> Source for StereoBalance<S>
+where
+    S: Source<Item = i16>,
+{
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        let pending = usize::from(self.pending_right_sample.is_some());
+        self.source.current_frame_len().map(|len| {
+            if self.source.channels() == 1 {
+                len * 2 + pending
+            } else {
+                len
+            }
+        })
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.source.channels().max(2)
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+impl<S> Iterator for StereoBalance<S>
+where
+    S: Source<Item = i16>,
+{
+    type Item = i16;
+
+    #[inline]
+    fn next(&mut self) -> Option<i16> {
+        if let Some(sample) = self.pending_right_sample.take() {
+            return Some(sample);
+        }
+
+        let sample = self.source.next()?;
+
+        // Query channels after `next()`, since the source may have just moved to a new frame
+        let channels = self.source.channels();
+        if channels != self.last_channels {
+            self.last_channels = channels;
+            self.channel_index = 0;
+        }
+
+        let (left_gain, right_gain) = get_channel_gains(self.balance.load(Ordering::Relaxed));
+
+        if channels <= 1 {
+            self.pending_right_sample = Some(apply_gain(sample, right_gain));
+            return Some(apply_gain(sample, left_gain));
+        }
+
+        let gain = match self.channel_index {
+            0 => left_gain,
+            1 => right_gain,
+            _ => 1.0,
+        };
+        self.channel_index = (self.channel_index + 1) % channels;
+
+        Some(apply_gain(sample, gain))
+    }
+}
+
+/// Map a balance between -9 and 9 to (left gain, right gain), each between 0 and 1.
+pub fn get_channel_gains(balance: i8) -> (f32, f32) {
+    let balance = balance.clamp(-9, 9) as f32 / 9_f32;
+    if balance > 0.0 {
+        (1.0 - balance, 1.0)
+    } else {
+        (1.0, 1.0 + balance)
+    }
+}
+
+fn apply_gain(sample: i16, gain: f32) -> i16 {
+    (sample as f32 * gain) as i16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::buffer::SamplesBuffer;
+
+    fn pan(channels: u16, samples: Vec<i16>, balance: i8) -> Vec<i16> {
+        let source = SamplesBuffer::new(channels, 44100, samples);
+        StereoBalance::new(source, Arc::new(AtomicI8::new(balance))).collect()
+    }
+
+    #[test]
+    fn channel_gains() {
+        assert_eq!(get_channel_gains(-9), (1.0, 0.0));
+        assert_eq!(get_channel_gains(0), (1.0, 1.0));
+        assert_eq!(get_channel_gains(9), (0.0, 1.0));
+        assert_eq!(get_channel_gains(-3), (1.0, 1.0 - 3.0 / 9.0));
+        // Out of range balance is clamped
+        assert_eq!(get_channel_gains(i8::MIN), (1.0, 0.0));
+        assert_eq!(get_channel_gains(i8::MAX), (0.0, 1.0));
+    }
+
+    #[test]
+    fn pan_stereo() {
+        let samples = vec![1000, 2000, -1000, -2000];
+        assert_eq!(pan(2, samples.clone(), -9), vec![1000, 0, -1000, 0]);
+        assert_eq!(pan(2, samples.clone(), 0), samples);
+        assert_eq!(pan(2, samples, 9), vec![0, 2000, 0, -2000]);
+    }
+
+    #[test]
+    fn upmix_mono() {
+        assert_eq!(pan(1, vec![1000, -1000], -9), vec![1000, 0, -1000, 0]);
+        assert_eq!(pan(1, vec![1000, -1000], 9), vec![0, 1000, 0, -1000]);
+    }
+}
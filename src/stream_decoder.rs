@@ -0,0 +1,124 @@
+//! Decoders for each stream format, chosen by the player at runtime,
+//! so the player itself doesn't care which format it's playing.
+
+use anyhow::{anyhow, Result};
+use std::io::Read;
+
+use crate::{
+    audio_backend::AudioSource, mp3_stream_decoder::Mp3StreamDecoder, player::StreamFormat,
+    symphonia_stream_decoder::SymphoniaStreamDecoder,
+};
+
+/// Bytes of a network stream, after ICY metadata is stripped.
+pub type StreamData = Box<dyn Read + Send>;
+
+/// Decodes a stream of one format into samples.
+pub trait StreamingDecoder: Sync {
+    #[cfg(test)]
+    fn format(&self) -> StreamFormat;
+
+    /// Start decoding `data`. Fails if the first audio frame can't be decoded.
+    fn decode(&self, data: StreamData) -> Result<AudioSource>;
+}
+
+struct Mp3Decoder;
+
+impl StreamingDecoder for Mp3Decoder {
+    #[cfg(test)]
+    fn format(&self) -> StreamFormat {
+        StreamFormat::Mp3
+    }
+
+    fn decode(&self, data: StreamData) -> Result<AudioSource> {
+        let source = Mp3StreamDecoder::new(data).map_err(|_| anyhow!("Not an MP3 stream"))?;
+        Ok(Box::new(source))
+    }
+}
+
+/// Formats supported by symphonia, like AAC and Ogg.
+struct SymphoniaDecoder {
+    format: StreamFormat,
+}
+
+impl StreamingDecoder for SymphoniaDecoder {
+    #[cfg(test)]
+    fn format(&self) -> StreamFormat {
+        self.format
+    }
+
+    fn decode(&self, data: StreamData) -> Result<AudioSource> {
+        // Hint for the container format
+        let extension = self.format.file_extension();
+        Ok(Box::new(SymphoniaStreamDecoder::new(data, extension)?))
+    }
+}
+
+static MP3_DECODER: Mp3Decoder = Mp3Decoder;
+static AAC_DECODER: SymphoniaDecoder = SymphoniaDecoder {
+    format: StreamFormat::Aac,
+};
+static OGG_DECODER: SymphoniaDecoder = SymphoniaDecoder {
+    format: StreamFormat::Ogg,
+};
+
+pub fn get_decoder(format: StreamFormat) -> &'static dyn StreamingDecoder {
+    match format {
+        StreamFormat::Mp3 => &MP3_DECODER,
+        StreamFormat::Aac => &AAC_DECODER,
+        StreamFormat::Ogg => &OGG_DECODER,
+    }
+}
+
+/// Get the decoder of a format by its name, like "mp3", as in the `format` field of a station.
+#[cfg(test)]
+pub fn get_decoder_by_name(name: &str) -> Result<&'static dyn StreamingDecoder> {
+    StreamFormat::from_name(name)
+        .map(get_decoder)
+        .ok_or_else(|| anyhow!("Unsupported stream format \"{}\"", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::Source;
+    use std::io::Cursor;
+
+    #[test]
+    fn get_mp3_decoder_by_name() {
+        let decoder = get_decoder_by_name("mp3").unwrap();
+        assert_eq!(decoder.format(), StreamFormat::Mp3);
+
+        let decoder = get_decoder_by_name("MP3").unwrap();
+        assert_eq!(decoder.format(), StreamFormat::Mp3);
+    }
+
+    #[test]
+    fn get_decoder_by_unknown_name() {
+        assert!(get_decoder_by_name("flac").is_err());
+        assert!(get_decoder_by_name("").is_err());
+    }
+
+    /// Two silent MPEG-1 Layer III frames, 128 kbps, 44.1 kHz, stereo.
+    const TWO_SILENT_MP3_FRAMES: &[u8] = include_bytes!("../tests/fixtures/two_silent_frames.mp3");
+
+    #[test]
+    fn count_down_mp3_frame_len() {
+        // 1152 samples per channel in each frame
+        const FRAME_SAMPLES: usize = 1152 * 2;
+
+        let decoder = get_decoder(StreamFormat::Mp3);
+        let mut source = decoder
+            .decode(Box::new(Cursor::new(TWO_SILENT_MP3_FRAMES)))
+            .unwrap();
+        assert_eq!(source.channels(), 2);
+        assert_eq!(source.current_frame_len(), Some(FRAME_SAMPLES));
+
+        for _frame in 0..2 {
+            for remaining in (0..FRAME_SAMPLES).rev() {
+                assert!(source.next().is_some());
+                assert_eq!(source.current_frame_len(), Some(remaining));
+            }
+        }
+        assert_eq!(source.next(), None);
+    }
+}
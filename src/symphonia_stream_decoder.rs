@@ -0,0 +1,157 @@
+use anyhow::{anyhow, Result};
+use rodio::Source;
+use std::{
+    io::{self, Read},
+    sync::Mutex,
+    time::Duration,
+};
+use symphonia::core::{
+    audio::{Channels, SampleBuffer, SignalSpec},
+    codecs::{Decoder, DecoderOptions, CODEC_TYPE_NULL},
+    errors::Error,
+    formats::{FormatOptions, FormatReader},
+    io::{MediaSourceStream, ReadOnlySource},
+    meta::MetadataOptions,
+    probe::Hint,
+};
+
+/// Decode a network stream in any format supported by symphonia, like AAC (ADTS) or Ogg Vorbis.
+///
+/// Like `Mp3StreamDecoder`, this doesn't require the "Seek" trait bound.
+pub struct SymphoniaStreamDecoder {
+    format_reader: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    spec: SignalSpec,
+    current_packet_samples: Vec<i16>,
+    current_packet_offset: usize,
+}
+
+impl SymphoniaStreamDecoder {
+    /// `extension` is a hint for the container format, like "aac" or "ogg".
+    /// Decodes the first packet, so this fails early if the stream can't be decoded.
+    pub fn new(data: impl Read + Send + 'static, extension: &str) -> Result<Self> {
+        let source = ReadOnlySource::new(SyncReader(Mutex::new(data)));
+        let media_source_stream = MediaSourceStream::new(Box::new(source), Default::default());
+
+        let mut hint = Hint::new();
+        hint.with_extension(extension);
+
+        let probe_result = symphonia::default::get_probe().format(
+            &hint,
+            media_source_stream,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )?;
+        let format_reader = probe_result.format;
+
+        let track = format_reader
+            .tracks()
+            .iter()
+            .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+            .ok_or_else(|| anyhow!("No audio track in stream"))?;
+        let track_id = track.id;
+        let decoder = symphonia::default::get_codecs()
+            .make(&track.codec_params, &DecoderOptions::default())?;
+
+        let mut stream_decoder = Self {
+            format_reader,
+            decoder,
+            track_id,
+            spec: SignalSpec::new(0, Channels::empty()),
+            current_packet_samples: Vec::new(),
+            current_packet_offset: 0,
+        };
+
+        if !stream_decoder.decode_next_packet() {
+            return Err(anyhow!("No audio in stream"));
+        }
+
+        Ok(stream_decoder)
+    }
+
+    /// Returns false if the stream has ended or can't be decoded anymore.
+    fn decode_next_packet(&mut self) -> bool {
+        loop {
+            let packet = match self.format_reader.next_packet() {
+                Ok(packet) => packet,
+                Err(_) => return false,
+            };
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+
+            match self.decoder.decode(&packet) {
+                Ok(decoded) => {
+                    if decoded.frames() == 0 {
+                        continue;
+                    }
+
+                    self.spec = *decoded.spec();
+                    let mut sample_buffer =
+                        SampleBuffer::<i16>::new(decoded.capacity() as u64, self.spec);
+                    sample_buffer.copy_interleaved_ref(decoded);
+
+                    self.current_packet_samples.clear();
+                    self.current_packet_samples
+                        .extend_from_slice(sample_buffer.samples());
+                    self.current_packet_offset = 0;
+                    return true;
+                }
+                // A corrupted packet. Skip it.
+                Err(Error::DecodeError(_)) => continue,
+                Err(_) => return false,
+            }
+        }
+    }
+}
+
+impl Source for SymphoniaStreamDecoder {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        Some(self.current_packet_samples.len() - self.current_packet_offset)
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.spec.channels.count() as _
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.spec.rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+impl Iterator for SymphoniaStreamDecoder {
+    type Item = i16;
+
+    #[inline]
+    fn next(&mut self) -> Option<i16> {
+        if self.current_packet_offset == self.current_packet_samples.len()
+            && !self.decode_next_packet()
+        {
+            return None;
+        }
+
+        let v = self.current_packet_samples[self.current_packet_offset];
+        self.current_packet_offset += 1;
+
+        Some(v)
+    }
+}
+
+/// symphonia requires its source to be `Sync`, which HTTP responses are not.
+/// Reading takes `&mut self`, so the mutex is never actually locked.
+struct SyncReader<R>(Mutex<R>);
+
+impl<R: Read> Read for SyncReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.get_mut().unwrap().read(buf)
+    }
+}
@@ -0,0 +1,148 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// A reader wrapper which copies everything read into a file, like the `tee` command.
+///
+/// Bytes are written as soon as they are read, so the file is complete whenever the reader is dropped.
+/// Failing to write the file only stops the recording, never the reading.
+///
+/// A new reader for the same target, like after reconnecting, appends to the same file.
+/// MP3 streams stay playable across the gap, since they are made of self-contained frames.
+pub struct TeeReader<R>
+where
+    R: Read,
+{
+    inner: R,
+    file: Option<File>,
+    target: RecordingTarget,
+    /// `RecordingTarget::switches` when `file` was opened
+    target_switches: u64,
+}
+
+/// Which file `TeeReader`s record to. Shared with the readers, so it can be switched while they read,
+/// like when the song changes.
+#[derive(Clone)]
+pub struct RecordingTarget(Arc<Mutex<RecordingTargetState>>);
+
+struct RecordingTargetState {
+    path: Option<PathBuf>,
+    /// How many times `path` has been switched
+    switches: u64,
+    /// When `path` was last written to, for logging the gap after reconnecting
+    last_written_at: Option<SystemTime>,
+}
+
+impl RecordingTarget {
+    /// Record to `path`, or nowhere if it's `None`.
+    pub fn new(path: Option<PathBuf>) -> Self {
+        Self(Arc::new(Mutex::new(RecordingTargetState {
+            path,
+            switches: 0,
+            last_written_at: None,
+        })))
+    }
+
+    /// Record to `path` from the next read on, appending to it. The current file is closed.
+    pub fn switch_to(&self, path: PathBuf) {
+        let mut state = self.0.lock().unwrap();
+        state.path = Some(path);
+        state.switches += 1;
+        state.last_written_at = None;
+    }
+
+    fn get(&self) -> (Option<PathBuf>, u64, Option<SystemTime>) {
+        let state = self.0.lock().unwrap();
+        (state.path.clone(), state.switches, state.last_written_at)
+    }
+
+    /// Note that the file opened at `switches` was written to, unless it has been switched since.
+    fn mark_written(&self, switches: u64) {
+        let mut state = self.0.lock().unwrap();
+        if state.switches == switches {
+            state.last_written_at = Some(SystemTime::now());
+        }
+    }
+
+    fn switches(&self) -> u64 {
+        self.0.lock().unwrap().switches
+    }
+}
+
+impl<R> TeeReader<R>
+where
+    R: Read,
+{
+    /// Append to the file of `target`. If there is none or it can't be opened, just pass bytes through.
+    pub fn new(inner: R, target: RecordingTarget) -> Self {
+        let (path, target_switches, last_written_at) = target.get();
+        let file = path.as_deref().and_then(open_recording_file);
+        if let (Some(path), Some(_), Some(last_written_at)) = (&path, &file, last_written_at) {
+            log_recording_gap(path, last_written_at);
+        }
+
+        Self {
+            inner,
+            file,
+            target,
+            target_switches,
+        }
+    }
+}
+
+impl<R> Read for TeeReader<R>
+where
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.inner.read(buf)?;
+
+        // Switch between reads, so the new file starts where decoding is at
+        if self.target.switches() != self.target_switches {
+            let (path, target_switches, _) = self.target.get();
+            self.file = path.as_deref().and_then(open_recording_file);
+            self.target_switches = target_switches;
+        }
+
+        if let Some(file) = &mut self.file {
+            match file.write_all(&buf[..len]) {
+                Ok(()) => self.target.mark_written(self.target_switches),
+                Err(e) => {
+                    log::error!("Failed to write recording, stopped recording: {}", e);
+                    self.file = None;
+                }
+            }
+        }
+
+        Ok(len)
+    }
+}
+
+fn open_recording_file(path: &Path) -> Option<File> {
+    match OpenOptions::new().create(true).append(true).open(path) {
+        Ok(file) => Some(file),
+        Err(e) => {
+            log::error!("Failed to open recording file {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// The recording has no audio between when it was last written to and now, like while reconnecting.
+fn log_recording_gap(path: &Path, last_written_at: SystemTime) {
+    let gap = last_written_at.elapsed().unwrap_or_default();
+    let since = last_written_at
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    log::warn!(
+        "Resumed recording to {} after a gap of {:.1}s since {}.{:03}",
+        path.display(),
+        gap.as_secs_f64(),
+        since.as_secs(),
+        since.subsec_millis()
+    );
+}
@@ -1,23 +1,139 @@
 use colored::Colorize;
-use console::Term;
+use console::{Key, Term};
 use once_cell::sync::Lazy;
-use std::fmt::Display;
+use std::{
+    borrow::Cow,
+    fmt::Display,
+    io::IsTerminal,
+    sync::atomic::{AtomicBool, Ordering},
+};
 
 static STDOUT: Lazy<Term> = Lazy::new(Term::stdout);
+static READING_CHAR: AtomicBool = AtomicBool::new(false);
 
-pub fn enable_color_on_windows() {
-    #[cfg(windows)]
-    colored::control::set_virtual_terminal(true).unwrap();
+/// Enable colored output only if the terminal supports it.
+///
+/// Colors are disabled if:
+/// - `NO_COLOR` environment variable is set (https://no-color.org)
+/// - `TERM` is `dumb`, or stdout is not a terminal
+/// - On Windows, the console doesn't support virtual terminal sequences (e.g. old versions of Windows 10)
+pub fn init_colors() {
+    if !terminal_supports_color() {
+        disable_colors();
+    } else {
+        #[cfg(windows)]
+        if colored::control::set_virtual_terminal(true).is_err() {
+            disable_colors();
+        }
+    }
+}
+
+fn terminal_supports_color() -> bool {
+    if std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+        return false;
+    }
+    if std::env::var("TERM").is_ok_and(|term| term == "dumb") {
+        return false;
+    }
+    console::colors_enabled()
+}
+
+pub fn disable_colors() {
+    colored::control::set_override(false);
+    console::set_colors_enabled(false);
+}
+
+/// Whether stdout is a terminal, rather than redirected to a file or a pipe.
+pub fn is_stdout_terminal() -> bool {
+    STDOUT.is_term()
 }
 
-pub fn read_char() -> std::io::Result<char> {
-    STDOUT.read_char()
+/// Whether keys can be read with `read_key`, which needs both stdin and stdout to be terminals.
+pub fn can_read_keys() -> bool {
+    STDOUT.is_term() && std::io::stdin().is_terminal()
+}
+
+/// Read a key press. Keys with Alt, like Alt+1, come as `Key::UnknownEscSeq`,
+/// since most terminals send them as Esc followed by the key.
+pub fn read_key() -> std::io::Result<Key> {
+    READING_CHAR.store(true, Ordering::SeqCst);
+    let result = STDOUT.read_key();
+    READING_CHAR.store(false, Ordering::SeqCst);
+    result
 }
 
 pub fn print_error(error: impl Display) {
     println!("{} {}", "Error:".bright_red(), error);
 }
 
+/// Set terminal window/tab title with OSC escape sequence. Does nothing if stdout is not a terminal.
+pub fn set_title(title: &str) {
+    if !STDOUT.is_term() {
+        return;
+    }
+    let title: String = title.chars().filter(|c| !c.is_control()).collect();
+    let _ = STDOUT.write_str(&format!("\x1b]0;{title}\x07"));
+}
+
+/// Elide `line` with "…" if it's wider than the terminal, so it doesn't wrap. Colors are kept.
+/// Returns `line` as is if stdout is not a terminal.
+pub fn fit_to_width(line: &str) -> Cow<'_, str> {
+    match STDOUT.size_checked() {
+        Some((_rows, columns)) => console::truncate_str(line, usize::from(columns), "…"),
+        None => Cow::Borrowed(line),
+    }
+}
+
+/// Call `on_resize` whenever the terminal is resized (SIGWINCH). Never returns.
+///
+/// On non-Unix OS, `on_resize` is never called.
+#[cfg_attr(not(unix), allow(unused_variables))]
+pub async fn watch_resize(on_resize: impl Fn()) {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::window_change()) {
+            Ok(mut resize_signal) => {
+                while resize_signal.recv().await.is_some() {
+                    on_resize();
+                }
+            }
+            Err(e) => log::warn!("Failed to listen to SIGWINCH: {}", e),
+        }
+    }
+
+    std::future::pending::<()>().await;
+}
+
+/// Ring the terminal bell. Does nothing if stdout is not a terminal.
+pub fn ring_bell() {
+    if STDOUT.is_term() {
+        let _ = STDOUT.write_str("\x07");
+    }
+}
+
+/// Save current terminal title to the terminal's title stack.
+/// The saved title will be restored when the returned `TitleGuard` drops.
+///
+/// Terminals not supporting title stack (XTWINOPS 22/23) will simply ignore these escape sequences.
+pub fn save_title() -> TitleGuard {
+    if STDOUT.is_term() {
+        let _ = STDOUT.write_str("\x1b[22;0t");
+    }
+    TitleGuard {}
+}
+
+pub struct TitleGuard {}
+
+impl Drop for TitleGuard {
+    fn drop(&mut self) {
+        if STDOUT.is_term() {
+            let _ = STDOUT.write_str("\x1b[23;0t");
+        }
+    }
+}
+
 /// You should create an instance of `CleanUpHelper` by calling this method when the programs starts.
 ///
 /// # The Problem
@@ -41,8 +157,13 @@ pub fn print_error(error: impl Display) {
 /// # The Workaround
 ///
 /// This method will create an instance of `CleanUpHelper` struct, which implements `Drop` trait.
-/// When it drops, it will send SIGINT (Ctrl+C) signal to the program itself on Unix-like OS, which fixes the bug.
+/// When it drops, if `read_key` is still blocking, it will send SIGINT (Ctrl+C) signal to the program itself on Unix-like OS, which fixes the bug.
 /// Rust's Drop trait will guarantee the method to be called.
+///
+/// If `read_key` is not blocking, the terminal is already in "canonical" mode, so the program can exit normally with its own exit code.
+///
+/// Some terminal multiplexers and custom signal handlers don't play well with the SIGINT,
+/// so users can opt out of this workaround with `--no-terminal-cleanup`.
 pub const fn create_clean_up_helper() -> CleanUpHelper {
     CleanUpHelper {}
 }
@@ -52,8 +173,10 @@ pub struct CleanUpHelper {}
 impl Drop for CleanUpHelper {
     fn drop(&mut self) {
         #[cfg(unix)]
-        unsafe {
-            libc::raise(libc::SIGINT);
+        if READING_CHAR.load(Ordering::SeqCst) {
+            unsafe {
+                libc::raise(libc::SIGINT);
+            }
         }
     }
 }
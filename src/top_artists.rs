@@ -0,0 +1,25 @@
+use crate::models::code_radio::SongHistory;
+use std::collections::HashMap;
+
+/// Count how many times each artist appears in `song_history`, and return the top `n` artists with their counts.
+///
+/// Artists are ranked by count in descending order. Ties are broken by artist name, so the result is deterministic.
+pub fn get_top_artists(song_history: &[SongHistory], n: usize) -> Vec<(String, usize)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for history in song_history {
+        let artist = history.song.artist.trim();
+        if !artist.is_empty() {
+            *counts.entry(artist).or_default() += 1;
+        }
+    }
+
+    let mut top_artists: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(artist, count)| (artist.to_owned(), count))
+        .collect();
+    top_artists.sort_by(|(artist_a, count_a), (artist_b, count_b)| {
+        count_b.cmp(count_a).then_with(|| artist_a.cmp(artist_b))
+    });
+    top_artists.truncate(n);
+    top_artists
+}
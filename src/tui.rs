@@ -0,0 +1,229 @@
+//! A full-screen interface for `--tui`, drawn in the terminal's alternate screen.
+//!
+//! This module only draws a `View` and reads keys.
+//! What to show and what each key does is decided in `main`, same as the line-based interface.
+
+use anyhow::Result;
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Gauge, List, ListItem, ListState, Paragraph},
+};
+use std::{
+    io::{self, Stdout},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+/// A one-line message at the bottom, like "Copied to clipboard".
+static STATUS: Mutex<Option<String>> = Mutex::new(None);
+
+/// What the pane below the current song shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pane {
+    SongHistory,
+    Lyrics,
+    Help,
+    /// A station picker
+    Stations,
+}
+
+impl Pane {
+    fn title(self) -> &'static str {
+        match self {
+            Pane::SongHistory => " Recently played ",
+            Pane::Lyrics => " Lyrics ",
+            Pane::Help => " Keyboard shortcuts ",
+            Pane::Stations => " Select a station (Up/Down to move, Enter to play, Esc to go back) ",
+        }
+    }
+}
+
+pub struct View {
+    pub station: String,
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    /// Like "Volume 5/9"
+    pub volume: String,
+    /// Like "Listeners: 12"
+    pub listeners: String,
+    /// Like "01:14 / 05:14"
+    pub progress_info: String,
+    /// Between 0 and 1. 0 if the song's duration is unknown.
+    pub progress_ratio: f64,
+    pub pane: Pane,
+    pub pane_lines: Vec<String>,
+    /// Highlighted line of the pane, for the station picker
+    pub selected_line: Option<usize>,
+}
+
+pub enum Key {
+    Char(char),
+    /// A character typed with Alt held, like Alt+1
+    Alt(char),
+    Up,
+    Down,
+    Enter,
+    Esc,
+    /// Ctrl+C doesn't send SIGINT in raw mode, so it comes as a key
+    Interrupt,
+}
+
+pub struct Tui {
+    terminal: Terminal<CrosstermBackend<Stdout>>,
+}
+
+impl Tui {
+    /// Switch to the alternate screen in raw mode. The terminal is restored when `Tui` drops.
+    pub fn enter() -> Result<Self> {
+        enable_raw_mode()?;
+        ACTIVE.store(true, Ordering::SeqCst);
+        execute!(io::stdout(), EnterAlternateScreen)?;
+
+        let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+        terminal.hide_cursor()?;
+        terminal.clear()?;
+        Ok(Self { terminal })
+    }
+
+    pub fn draw(&mut self, view: &View) -> Result<()> {
+        let status = STATUS.lock().unwrap().clone();
+        self.terminal
+            .draw(|frame| render(frame, view, status.as_deref()))?;
+        Ok(())
+    }
+
+    /// Wait up to `timeout` for a key press.
+    pub fn read_key(&self, timeout: Duration) -> Result<Option<Key>> {
+        if !event::poll(timeout)? {
+            return Ok(None);
+        }
+
+        let key_event = match event::read()? {
+            // Windows also reports key releases
+            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => key_event,
+            _ => return Ok(None),
+        };
+
+        let key = match key_event.code {
+            KeyCode::Char('c') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                Key::Interrupt
+            }
+            KeyCode::Char(c) if key_event.modifiers.contains(KeyModifiers::ALT) => Key::Alt(c),
+            KeyCode::Char(c) => Key::Char(c),
+            KeyCode::Up => Key::Up,
+            KeyCode::Down => Key::Down,
+            KeyCode::Enter => Key::Enter,
+            KeyCode::Esc => Key::Esc,
+            _ => return Ok(None),
+        };
+        Ok(Some(key))
+    }
+}
+
+impl Drop for Tui {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+pub fn is_active() -> bool {
+    ACTIVE.load(Ordering::SeqCst)
+}
+
+/// Leave the alternate screen and raw mode. Call this before exiting the process while `Tui` may be alive.
+/// Does nothing if the terminal is already restored.
+pub fn restore_terminal() {
+    if !ACTIVE.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    if let Err(e) = disable_raw_mode() {
+        log::warn!("Failed to disable raw mode: {:#}", e);
+    }
+    if let Err(e) = execute!(io::stdout(), LeaveAlternateScreen, crossterm::cursor::Show) {
+        log::warn!("Failed to leave alternate screen: {:#}", e);
+    }
+}
+
+/// Show `text` in the status line, in place of printing it above the progress bar.
+pub fn set_status(text: &str) {
+    let status = console::strip_ansi_codes(text)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    STATUS.lock().unwrap().replace(status);
+}
+
+fn render<B: Backend>(frame: &mut Frame<B>, view: &View, status: Option<&str>) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(5), // Song
+            Constraint::Length(3), // Progress
+            Constraint::Min(3),    // Pane
+            Constraint::Length(1), // Status
+        ])
+        .split(frame.size());
+
+    let label_style = Style::default().fg(Color::LightGreen);
+    let song = Paragraph::new(vec![
+        Line::from(vec![
+            Span::styled("Song:   ", label_style),
+            Span::raw(&view.title),
+        ]),
+        Line::from(vec![
+            Span::styled("Artist: ", label_style),
+            Span::raw(&view.artist),
+        ]),
+        Line::from(vec![
+            Span::styled("Album:  ", label_style),
+            Span::raw(&view.album),
+        ]),
+    ])
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" {} ", view.station)),
+    );
+    frame.render_widget(song, chunks[0]);
+
+    let progress = Gauge::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" {} - {} ", view.volume, view.listeners)),
+        )
+        .gauge_style(Style::default().fg(Color::LightGreen))
+        .ratio(view.progress_ratio.clamp(0.0, 1.0))
+        .label(view.progress_info.as_str());
+    frame.render_widget(progress, chunks[1]);
+
+    let pane_items: Vec<ListItem> = view
+        .pane_lines
+        .iter()
+        .map(|line| ListItem::new(line.as_str()))
+        .collect();
+    let pane = List::new(pane_items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(view.pane.title()),
+        )
+        .highlight_style(Style::default().fg(Color::Black).bg(Color::LightGreen))
+        .highlight_symbol("> ");
+    let mut pane_state = ListState::default().with_selected(view.selected_line);
+    frame.render_stateful_widget(pane, chunks[2], &mut pane_state);
+
+    let status = Paragraph::new(status.unwrap_or("Press ? for keyboard shortcuts, Q to quit"))
+        .style(Style::default().fg(Color::Yellow));
+    frame.render_widget(status, chunks[3]);
+}
@@ -1,21 +1,44 @@
 use anyhow::Result;
 use once_cell::sync::Lazy;
+use reqwest::{Response, StatusCode};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::{
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 use tokio::sync::Mutex;
 use version_compare::Version;
 
-const LATEST_RELEASE_CACHE_FILE_NAME: &str = "e128c5f5-0a56-41d3-a121-1f2c8bb88417";
+use crate::{app_dirs, http_client};
+
+const LATEST_RELEASE_CACHE_FILE_NAME: &str = "latest-release.json";
+const RATE_LIMIT_RESET_CACHE_FILE_NAME: &str = "github-rate-limit-reset";
+
+/// Cache files in temp dir from older versions, which are moved to the cache directory
+const LEGACY_LATEST_RELEASE_CACHE_FILE_NAME: &str = "e128c5f5-0a56-41d3-a121-1f2c8bb88417";
+const LEGACY_RATE_LIMIT_RESET_CACHE_FILE_NAME: &str =
+    "e128c5f5-0a56-41d3-a121-1f2c8bb88417-rate-limit";
+
+/// How long to stop calling GitHub API if it rate-limits us without telling when the limit resets.
+const DEFAULT_RATE_LIMIT_BACKOFF: Duration = Duration::from_secs(60 * 60);
 
 static LATEST_RELEASE_CACHE_FILE_PATH: Lazy<PathBuf> = Lazy::new(|| {
-    let mut pathbuf = std::env::temp_dir();
-    pathbuf.push(LATEST_RELEASE_CACHE_FILE_NAME);
-    pathbuf
+    get_cache_file_path(
+        LATEST_RELEASE_CACHE_FILE_NAME,
+        LEGACY_LATEST_RELEASE_CACHE_FILE_NAME,
+    )
+});
+
+static RATE_LIMIT_RESET_CACHE_FILE_PATH: Lazy<PathBuf> = Lazy::new(|| {
+    get_cache_file_path(
+        RATE_LIMIT_RESET_CACHE_FILE_NAME,
+        LEGACY_RATE_LIMIT_RESET_CACHE_FILE_NAME,
+    )
 });
 
 static FILE_IO_MUTEX: Mutex<()> = Mutex::const_new(());
 
-// Use a cache file in temp dir to store latest release info and speed up the process of checking update
+// Use a cache file in cache dir to store latest release info and speed up the process of checking update
 pub async fn get_new_release() -> Result<Option<Release>> {
     // Asynchronously fetch latest release info from GitHub, and save it to cache file
     let get_new_release_from_github_task = tokio::spawn(get_new_release_from_github());
@@ -29,7 +52,16 @@ pub async fn get_new_release() -> Result<Option<Release>> {
 }
 
 async fn get_new_release_from_github() -> Result<Option<Release>> {
-    let latest_release_from_github = get_latest_release_from_github().await?;
+    if let Some(rate_limit_reset_timestamp) = try_read_rate_limit_reset_from_cache_file().await {
+        if get_unix_timestamp() < rate_limit_reset_timestamp {
+            return Ok(None);
+        }
+    }
+
+    let latest_release_from_github = match get_latest_release_from_github().await? {
+        Some(latest_release_from_github) => latest_release_from_github,
+        None => return Ok(None), // Rate-limited by GitHub
+    };
     if release_newer_than_current_package(&latest_release_from_github) {
         Ok(Some(latest_release_from_github))
     } else {
@@ -37,17 +69,25 @@ async fn get_new_release_from_github() -> Result<Option<Release>> {
     }
 }
 
-async fn get_latest_release_from_github() -> Result<Release> {
-    let latest_github_response: GithubRelease = reqwest::Client::new()
+/// Returns `None` if GitHub API rate-limits us.
+async fn get_latest_release_from_github() -> Result<Option<Release>> {
+    let response = http_client::client()
         .get("https://api.github.com/repos/JasonWei512/code-radio-cli/releases/latest")
         .header(
             "User-Agent",
             "https://github.com/JasonWei512/code-radio-cli",
         )
+        .timeout(http_client::request_timeout())
         .send()
-        .await?
-        .json()
-        .await?;
+        .await
+        .map_err(http_client::explain_timeout)?;
+
+    if let Some(rate_limit_reset_timestamp) = get_rate_limit_reset_timestamp(&response) {
+        let _ = write_rate_limit_reset_to_cache_file(rate_limit_reset_timestamp).await;
+        return Ok(None);
+    }
+
+    let latest_github_response: GithubRelease = response.error_for_status()?.json().await?;
 
     let latest_release = Release {
         version: latest_github_response.tag_name.chars().skip(1).collect(),
@@ -56,7 +96,37 @@ async fn get_latest_release_from_github() -> Result<Release> {
 
     let _ = write_latest_release_to_cache_file(&latest_release).await;
 
-    Ok(latest_release)
+    Ok(Some(latest_release))
+}
+
+/// GitHub's unauthenticated API responds with 403 or 429 when the rate limit is exceeded:
+/// https://docs.github.com/en/rest/overview/resources-in-the-rest-api#rate-limiting
+///
+/// If the response is rate-limited, returns the unix timestamp (in seconds) when we can call the API again.
+fn get_rate_limit_reset_timestamp(response: &Response) -> Option<u64> {
+    let get_header = |name| get_header_value(response, name);
+
+    let rate_limited = match response.status() {
+        StatusCode::TOO_MANY_REQUESTS => true,
+        StatusCode::FORBIDDEN => {
+            get_header("x-ratelimit-remaining") == Some("0") || get_header("retry-after").is_some()
+        }
+        _ => false,
+    };
+    if !rate_limited {
+        return None;
+    }
+
+    if let Some(retry_after_seconds) = get_header("retry-after").and_then(|h| h.parse::<u64>().ok())
+    {
+        return Some(get_unix_timestamp() + retry_after_seconds);
+    }
+    if let Some(reset_timestamp) =
+        get_header("x-ratelimit-reset").and_then(|h| h.parse::<u64>().ok())
+    {
+        return Some(reset_timestamp);
+    }
+    Some(get_unix_timestamp() + DEFAULT_RATE_LIMIT_BACKOFF.as_secs())
 }
 
 async fn try_read_latest_release_from_cache_file() -> Option<Release> {
@@ -81,6 +151,55 @@ async fn write_latest_release_to_cache_file(release: &Release) -> Result<()> {
     Ok(())
 }
 
+async fn try_read_rate_limit_reset_from_cache_file() -> Option<u64> {
+    let _file_io_mutex_guard = FILE_IO_MUTEX.lock().await;
+
+    let cache_file_content = tokio::fs::read_to_string(RATE_LIMIT_RESET_CACHE_FILE_PATH.as_path())
+        .await
+        .ok()?;
+    cache_file_content.trim().parse().ok()
+}
+
+async fn write_rate_limit_reset_to_cache_file(rate_limit_reset_timestamp: u64) -> Result<()> {
+    let _file_io_mutex_guard = FILE_IO_MUTEX.lock().await;
+
+    tokio::fs::write(
+        &RATE_LIMIT_RESET_CACHE_FILE_PATH.as_path(),
+        rate_limit_reset_timestamp.to_string().as_bytes(),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Path of a cache file in the cache directory.
+/// If it doesn't exist there yet, move the legacy one from temp dir, or remove it if it can't be moved.
+fn get_cache_file_path(file_name: &str, legacy_file_name: &str) -> PathBuf {
+    let cache_file_path = app_dirs::cache_dir().join(file_name);
+    let legacy_cache_file_path = std::env::temp_dir().join(legacy_file_name);
+
+    if legacy_cache_file_path.exists()
+        && !cache_file_path.exists()
+        && std::fs::rename(&legacy_cache_file_path, &cache_file_path).is_ok()
+    {
+        return cache_file_path;
+    }
+    let _ = std::fs::remove_file(&legacy_cache_file_path);
+
+    cache_file_path
+}
+
+fn get_header_value<'a>(response: &'a Response, name: &str) -> Option<&'a str> {
+    response.headers().get(name)?.to_str().ok()
+}
+
+fn get_unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
 fn release_newer_than_current_package(release: &Release) -> bool {
     if let Some(current_version) = Version::from(env!("CARGO_PKG_VERSION")) {
         if let Some(release_version) = Version::from(&release.version) {
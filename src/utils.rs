@@ -2,6 +2,122 @@ pub fn humanize_seconds_to_minutes_and_seconds(seconds: u64) -> String {
     format!("{:02}:{:02}", seconds / 60, seconds % 60)
 }
 
+/// Clean up song metadata text for display:
+/// - Strip leading UTF-8 BOM, including a BOM which was mis-decoded as Latin-1 ("ï»¿")
+/// - Replace control characters, which could mess up the terminal, with U+FFFD
+pub fn sanitize_metadata_text(text: &str) -> String {
+    text.trim_start_matches('\u{feff}')
+        .trim_start_matches("\u{ef}\u{bb}\u{bf}")
+        .chars()
+        .map(|c| {
+            if c.is_control() {
+                char::REPLACEMENT_CHARACTER
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Replace each `{name}` in `template` with its value in `values`, in a single pass,
+/// so values which look like placeholders are kept as is.
+/// Unknown placeholders are kept as is too, so typos are easy to spot.
+pub fn render_template(template: &str, values: &[(&str, &str)]) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let value = rest.find('}').and_then(|end| {
+            let name = &rest[1..end];
+            values
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| (*value, end))
+        });
+        match value {
+            Some((value, end)) => {
+                rendered.push_str(value);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                rendered.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    rendered.push_str(rest);
+
+    rendered
+}
+
+/// Draw `values` as a sparkline of block characters, one per value,
+/// scaled from the lowest to the highest value. All blocks are the lowest if the values are all the same.
+pub fn render_sparkline(values: &[i64]) -> String {
+    const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+    let min = values.iter().copied().min().unwrap_or_default();
+    let max = values.iter().copied().max().unwrap_or_default();
+    let range = (max - min).max(1) as f64;
+
+    values
+        .iter()
+        .map(|value| {
+            let level = ((value - min) as f64 / range * (BLOCKS.len() - 1) as f64).round();
+            BLOCKS[level as usize]
+        })
+        .collect()
+}
+
+/// Make `name` safe as a file name on every OS: Replace characters Windows doesn't allow,
+/// and control characters, with `_`, trim trailing dots and spaces, and cap the length.
+/// `_` if nothing is left.
+pub fn sanitize_file_name(name: &str) -> String {
+    // Most file systems allow 255 bytes, so leave room for a counter and an extension
+    const MAX_LEN: usize = 200;
+
+    let mut sanitized = String::with_capacity(name.len().min(MAX_LEN));
+    for c in name.trim().chars() {
+        let c = match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        };
+        if sanitized.len() + c.len_utf8() > MAX_LEN {
+            break;
+        }
+        sanitized.push(c);
+    }
+
+    let sanitized = sanitized.trim_end_matches(['.', ' ']);
+    if sanitized.is_empty() {
+        "_".to_owned()
+    } else {
+        sanitized.to_owned()
+    }
+}
+
+/// Levenshtein distance between two strings, counted in chars.
+pub fn get_edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous_row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, char_a) in a.chars().enumerate() {
+        let mut current_row = vec![i + 1; b.len() + 1];
+        for (j, char_b) in b.iter().enumerate() {
+            let substitution_cost = usize::from(char_a != *char_b);
+            current_row[j + 1] = (previous_row[j] + substitution_cost)
+                .min(previous_row[j + 1] + 1)
+                .min(current_row[j] + 1);
+        }
+        previous_row = current_row;
+    }
+
+    previous_row[b.len()]
+}
+
 pub fn get_current_executable_name() -> String {
     if let Some(executable_name) = try_get_current_executable_name() {
         return executable_name;
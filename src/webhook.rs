@@ -0,0 +1,49 @@
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::{http_client, models::code_radio::Song, utils};
+
+/// JSON body posted to `--webhook` on each song change.
+#[derive(Serialize)]
+pub struct SongChangePayload {
+    pub station: String,
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub listeners: i64,
+}
+
+impl SongChangePayload {
+    pub fn new(station: &str, song: &Song, listeners: i64) -> Self {
+        Self {
+            station: station.to_owned(),
+            title: utils::sanitize_metadata_text(&song.title),
+            artist: utils::sanitize_metadata_text(&song.artist),
+            album: utils::sanitize_metadata_text(&song.album),
+            listeners,
+        }
+    }
+}
+
+/// POST `payload` as JSON to `url` in background.
+/// Failures are only logged, so a broken endpoint never interrupts playback.
+pub fn post_song_change(url: &str, payload: SongChangePayload) {
+    let url = url.to_owned();
+    tokio::spawn(async move {
+        if let Err(e) = post(&url, &payload).await {
+            log::warn!("Failed to post webhook to {}: {:#}", url, e);
+        }
+    });
+}
+
+async fn post(url: &str, payload: &SongChangePayload) -> Result<()> {
+    http_client::client()
+        .post(url)
+        .timeout(http_client::request_timeout())
+        .json(payload)
+        .send()
+        .await
+        .map_err(http_client::explain_timeout)?
+        .error_for_status()?;
+    Ok(())
+}
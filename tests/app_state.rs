@@ -0,0 +1,103 @@
+use code_radio_cli::{
+    app_state::{AppState, UiEvent},
+    models::code_radio::{CodeRadioMessage, Listeners},
+};
+
+fn message(song_id: &str, elapsed: i64, listeners: i64) -> CodeRadioMessage {
+    let mut message = CodeRadioMessage::default();
+    message.now_playing.song.id = song_id.to_owned();
+    message.now_playing.elapsed = elapsed;
+    message.now_playing.duration = 200;
+    message.listeners.current = listeners;
+    message
+}
+
+fn listeners(current: i64) -> Listeners {
+    Listeners {
+        current,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn first_message_is_first_song() {
+    let mut state = AppState::new();
+    let first = message("a", 10, 5);
+
+    assert_eq!(
+        state.on_message(first.clone()),
+        vec![
+            UiEvent::NewSong {
+                song: first.now_playing.song.clone(),
+                is_first: true
+            },
+            UiEvent::ListenersChanged(listeners(5)),
+        ]
+    );
+    assert_eq!(state.current_song_id(), Some("a"));
+    assert_eq!(state.latest_message(), Some(&first));
+}
+
+#[test]
+fn same_song_reports_progress() {
+    let mut state = AppState::new();
+    state.on_message(message("a", 10, 5));
+
+    assert_eq!(
+        state.on_message(message("a", 25, 5)),
+        vec![UiEvent::SongProgress {
+            elapsed: 25,
+            duration: 200
+        }]
+    );
+    assert_eq!(
+        state.on_message(message("a", 40, 6)),
+        vec![
+            UiEvent::SongProgress {
+                elapsed: 40,
+                duration: 200
+            },
+            UiEvent::ListenersChanged(listeners(6)),
+        ]
+    );
+}
+
+#[test]
+fn song_change_is_not_first_song() {
+    let mut state = AppState::new();
+    state.on_message(message("a", 10, 5));
+
+    let next = message("b", 0, 5);
+    assert_eq!(
+        state.on_message(next.clone()),
+        vec![UiEvent::NewSong {
+            song: next.now_playing.song,
+            is_first: false
+        }]
+    );
+    assert_eq!(state.current_song_id(), Some("b"));
+}
+
+#[test]
+fn live_broadcast_transitions() {
+    let mut state = AppState::new();
+    state.on_message(message("a", 10, 5));
+
+    let mut live = message("a", 20, 5);
+    live.live.is_live = true;
+    live.live.streamer_name = "DJ".to_owned();
+    let events = state.on_message(live.clone());
+    assert_eq!(
+        events[0],
+        UiEvent::LiveStarted {
+            streamer_name: "DJ".to_owned()
+        }
+    );
+
+    // Still live, so nothing to announce
+    let events = state.on_message(live);
+    assert_eq!(events.len(), 1);
+
+    let events = state.on_message(message("a", 40, 5));
+    assert_eq!(events[0], UiEvent::LiveEnded);
+}
@@ -0,0 +1,32 @@
+use code_radio_cli::utils::sanitize_file_name;
+
+#[test]
+fn keep_safe_names() {
+    assert_eq!(
+        sanitize_file_name("01 - Artist - Title"),
+        "01 - Artist - Title"
+    );
+    assert_eq!(sanitize_file_name("Café ☕"), "Café ☕");
+}
+
+#[test]
+fn replace_unsafe_characters() {
+    assert_eq!(sanitize_file_name("AC/DC: Back?"), "AC_DC_ Back_");
+    assert_eq!(sanitize_file_name("a\\b|c*d\"e<f>g"), "a_b_c_d_e_f_g");
+    assert_eq!(sanitize_file_name("line\nbreak"), "line_break");
+}
+
+#[test]
+fn trim_trailing_dots_and_spaces() {
+    assert_eq!(sanitize_file_name("Title... "), "Title");
+    assert_eq!(sanitize_file_name("..."), "_");
+    assert_eq!(sanitize_file_name(""), "_");
+}
+
+#[test]
+fn cap_long_names() {
+    let name = "é".repeat(300);
+    let sanitized = sanitize_file_name(&name);
+    assert!(sanitized.len() <= 200);
+    assert!(sanitized.chars().all(|c| c == 'é'));
+}
@@ -0,0 +1,25 @@
+use code_radio_cli::utils::sanitize_metadata_text;
+
+#[test]
+fn keep_clean_text() {
+    assert_eq!(sanitize_metadata_text("Artist - Title"), "Artist - Title");
+    assert_eq!(sanitize_metadata_text("Café ☕"), "Café ☕");
+}
+
+#[test]
+fn strip_leading_bom() {
+    assert_eq!(sanitize_metadata_text("\u{feff}Title"), "Title");
+    // A UTF-8 BOM mis-decoded as Latin-1
+    assert_eq!(sanitize_metadata_text("ï»¿Title"), "Title");
+    // Only leading ones
+    assert_eq!(sanitize_metadata_text("Ti\u{feff}tle"), "Ti\u{feff}tle");
+}
+
+#[test]
+fn replace_control_characters() {
+    assert_eq!(sanitize_metadata_text("Title\u{1b}[2J"), "Title\u{fffd}[2J");
+    assert_eq!(
+        sanitize_metadata_text("a\nb\tc\0"),
+        "a\u{fffd}b\u{fffd}c\u{fffd}"
+    );
+}
@@ -0,0 +1,70 @@
+use code_radio_cli::{code_radio_api, models::code_radio::Remote};
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const NOWPLAYING_STATIC: &str = include_str!("fixtures/nowplaying_static.json");
+
+// `code_radio_api::set_api_url` can only be called once per process,
+// so this file has one mock server and one test.
+#[tokio::test]
+async fn get_message_and_stations() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/nowplaying_static/coderadio.json"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_raw(NOWPLAYING_STATIC, "application/json"),
+        )
+        .mount(&server)
+        .await;
+    code_radio_api::set_api_url(&server.uri(), "coderadio").unwrap();
+
+    let message = code_radio_api::get_message().await.unwrap();
+
+    assert_eq!(message.station.id, 2);
+    assert_eq!(message.station.name, "freeCodeCamp.org Code Radio");
+    assert_eq!(
+        message.station.listen_url,
+        "https://coderadio-admin-v2.freecodecamp.org/listen/coderadio/radio.mp3"
+    );
+    assert_eq!(message.station.mounts.len(), 2);
+    assert_eq!(message.station.mounts[1].name, "64kbps MP3");
+    assert_eq!(message.station.mounts[1].bitrate, 64);
+    assert_eq!(message.listeners.current, 39);
+    assert!(message.is_online);
+
+    let now_playing = &message.now_playing;
+    assert_eq!(now_playing.song.title, "Days Gone (Emancipator Remix)");
+    assert_eq!(now_playing.song.artist, "Tor");
+    assert_eq!(now_playing.song.album, "Blue Book Remixed");
+    assert_eq!(now_playing.duration, 344);
+    assert_eq!(now_playing.elapsed, 284);
+    assert_eq!(now_playing.remaining, 60);
+
+    assert_eq!(message.playing_next.song.title, "Glisten");
+    assert_eq!(message.song_history.len(), 5);
+    assert_eq!(message.song_history[0].song.title, "Evil Needle - Angel");
+
+    let stations = code_radio_api::get_stations().await.unwrap();
+    let station_ids: Vec<i64> = stations.iter().map(|s| s.id).collect();
+    assert_eq!(station_ids, [2, 3]);
+
+    // Remotes are merged with mounts, sorted by ID
+    let mut message = message;
+    message.station.remotes.push(Remote {
+        id: 4,
+        name: "Remote 4".to_owned(),
+        ..Default::default()
+    });
+    message.station.remotes.push(Remote {
+        id: 1,
+        name: "Remote 1".to_owned(),
+        ..Default::default()
+    });
+    let stations = code_radio_api::get_stations_from_message(&message);
+    let station_ids: Vec<i64> = stations.iter().map(|s| s.id).collect();
+    assert_eq!(station_ids, [1, 2, 3, 4]);
+    assert_eq!(stations[0].name, "Remote 1");
+    assert_eq!(stations[1].name, "/radio.mp3 (128kbps MP3)");
+}
@@ -0,0 +1,40 @@
+use code_radio_cli::code_radio_api;
+use futures_util::StreamExt;
+use std::time::Duration;
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const NOWPLAYING_SSE: &str = include_str!("fixtures/nowplaying_sse.txt");
+
+// `code_radio_api::set_api_url` can only be called once per process,
+// so this file has one mock server and one test.
+#[tokio::test]
+async fn get_message_stream() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/live/nowplaying/sse"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(NOWPLAYING_SSE, "text/event-stream"))
+        .mount(&server)
+        .await;
+    code_radio_api::set_api_url(&server.uri(), "coderadio").unwrap();
+
+    let mut message_stream = code_radio_api::get_message_stream();
+
+    // The "connect" and empty ping events are skipped
+    let message = tokio::time::timeout(Duration::from_secs(10), message_stream.next())
+        .await
+        .expect("Timed out waiting for a message")
+        .expect("Stream ended without a message")
+        .unwrap();
+
+    assert_eq!(message.station.shortcode, "coderadio");
+    assert_eq!(message.now_playing.song.title, "West Lake");
+    assert_eq!(
+        message.now_playing.song.id,
+        "ec8eac58ccf43fcbd92a9164b69191c9"
+    );
+    // Heartbeats are not parse failures
+    assert_eq!(code_radio_api::consecutive_parse_failures(), 0);
+}
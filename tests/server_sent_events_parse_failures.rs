@@ -0,0 +1,41 @@
+use code_radio_cli::code_radio_api;
+use futures_util::StreamExt;
+use std::time::Duration;
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+// A partial message, a message of an unexpected format, then a valid one
+const MALFORMED_SSE: &str = r#"data: {"channel":"station:coderadio","pub":{"data":{"np":{"station"
+
+data: {"channel":"station:coderadio","pub":{"data":{"np":"unexpected"}}}
+
+data: {"channel":"station:coderadio","pub":{"data":{"np":{"now_playing":{"song":{"title":"Title"}}}}}}
+
+"#;
+
+// `code_radio_api::set_api_url` can only be called once per process,
+// so this file has one mock server and one test.
+#[tokio::test]
+async fn skip_malformed_messages() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/live/nowplaying/sse"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(MALFORMED_SSE, "text/event-stream"))
+        .mount(&server)
+        .await;
+    code_radio_api::set_api_url(&server.uri(), "coderadio").unwrap();
+
+    let mut message_stream = code_radio_api::get_message_stream();
+
+    let message = tokio::time::timeout(Duration::from_secs(10), message_stream.next())
+        .await
+        .expect("Timed out waiting for a message")
+        .expect("Stream ended without a message")
+        .unwrap();
+
+    assert_eq!(message.now_playing.song.title, "Title");
+    // Reset by the valid message
+    assert_eq!(code_radio_api::consecutive_parse_failures(), 0);
+}
@@ -0,0 +1,21 @@
+use code_radio_cli::utils::render_sparkline;
+
+#[test]
+fn render_from_lowest_to_highest() {
+    assert_eq!(render_sparkline(&[0, 7, 14]), "▁▅█");
+    assert_eq!(
+        render_sparkline(&[10, 11, 12, 13, 14, 15, 16, 17]),
+        "▁▂▃▄▅▆▇█"
+    );
+}
+
+#[test]
+fn render_same_values_as_lowest() {
+    assert_eq!(render_sparkline(&[5, 5, 5]), "▁▁▁");
+    assert_eq!(render_sparkline(&[5]), "▁");
+}
+
+#[test]
+fn render_nothing() {
+    assert_eq!(render_sparkline(&[]), "");
+}
@@ -0,0 +1,36 @@
+use code_radio_cli::utils::render_template;
+
+#[test]
+fn render_known_placeholders() {
+    let values = [("artist", "Artist"), ("title", "Title")];
+    assert_eq!(
+        render_template("{artist} - {title}", &values),
+        "Artist - Title"
+    );
+    assert_eq!(render_template("{title}{title}", &values), "TitleTitle");
+}
+
+#[test]
+fn keep_unknown_placeholders() {
+    let values = [("title", "Title")];
+    assert_eq!(
+        render_template("{titel} {title} {", &values),
+        "{titel} Title {"
+    );
+    assert_eq!(render_template("{{title}}", &values), "{Title}");
+}
+
+#[test]
+fn render_empty_values() {
+    let values = [("album", ""), ("title", "Title")];
+    assert_eq!(render_template("{title} ({album})", &values), "Title ()");
+}
+
+#[test]
+fn keep_values_which_look_like_placeholders() {
+    let values = [("artist", "{title}"), ("title", "Title")];
+    assert_eq!(
+        render_template("{artist}: {title}", &values),
+        "{title}: Title"
+    );
+}